@@ -0,0 +1,45 @@
+//! Compares `Instant::now` with looking up `globalThis.performance` on every
+//! call, as previous versions did.
+//!
+//! Run with `cargo bench --target wasm32-unknown-unknown` and
+//! `wasm-bindgen-test-runner` as the runner.
+#![cfg(not(target_os = "wasi"))]
+
+use std::hint::black_box;
+
+use js_sys::Object;
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast};
+use wasm_bindgen_test::{wasm_bindgen_bench, Criterion};
+use wasmtimer::std::{Instant, SystemTime};
+
+// The imports of previous versions. The types are renamed, as imports that
+// are identical to the ones of the crate clash with them when linking.
+#[wasm_bindgen]
+extern "C" {
+    type UncachedGlobalScope;
+
+    type UncachedPerformance;
+
+    #[wasm_bindgen(structural, method, getter, js_name = "performance")]
+    fn performance(this: &UncachedGlobalScope) -> UncachedPerformance;
+
+    #[wasm_bindgen(method, js_name = "now")]
+    fn now(this: &UncachedPerformance) -> f64;
+}
+
+fn uncached_performance_now() -> f64 {
+    let global_this: Object = js_sys::global();
+    let global_scope = global_this.unchecked_ref::<UncachedGlobalScope>();
+    global_scope.performance().now()
+}
+
+#[wasm_bindgen_bench]
+fn bench_now(c: &mut Criterion) {
+    c.bench_function("uncached performance.now()", |b| {
+        b.iter(|| black_box(uncached_performance_now()))
+    });
+    c.bench_function("Instant::now()", |b| b.iter(|| black_box(Instant::now())));
+    c.bench_function("SystemTime::now()", |b| {
+        b.iter(|| black_box(SystemTime::now()))
+    });
+}
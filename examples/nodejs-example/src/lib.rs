@@ -6,7 +6,6 @@ use wasm_bindgen::prelude::*;
 use wasmtimer::std::Instant;
 use wasmtimer::tokio::{interval, sleep, sleep_until, timeout};
 use web_sys::console::log_1;
-use web_sys::window;
 
 #[wasm_bindgen]
 pub async fn sleep_test() {
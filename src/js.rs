@@ -1,4 +1,5 @@
-use js_sys::Object;
+#[cfg(feature = "tokio")]
+use js_sys::Function;
 use wasm_bindgen::{prelude::wasm_bindgen, JsCast};
 
 #[wasm_bindgen]
@@ -10,22 +11,111 @@ extern "C" {
     #[wasm_bindgen(structural, method, getter, js_name = "performance")]
     pub fn performance(this: &GlobalScope) -> Performance;
 
-    #[wasm_bindgen(method, js_name = "now")]
-    pub fn now(this: &Performance) -> f64;
+    #[wasm_bindgen(method, getter, js_name = "timeOrigin")]
+    pub fn time_origin(this: &Performance) -> f64;
 
-    #[cfg(feature = "tokio")]
-    #[wasm_bindgen(catch , method, js_name = setTimeout)]
-    pub fn set_timeout_with_callback_and_timeout_and_arguments_0(
-        this: &GlobalScope,
-        handler: &::js_sys::Function,
-        timeout: i32,
+    // Imported directly so that reading the clock is a single call into JS,
+    // without passing a `Performance` handle around.
+    #[wasm_bindgen(js_namespace = performance, js_name = "now")]
+    pub fn performance_now() -> f64;
+
+    #[cfg(feature = "profile")]
+    #[wasm_bindgen(catch, js_namespace = performance, js_name = "mark")]
+    pub fn performance_mark(name: &str) -> Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue>;
+
+    #[cfg(feature = "profile")]
+    #[wasm_bindgen(catch, js_namespace = performance, js_name = "measure")]
+    pub fn performance_measure(
+        name: &str,
+        start_mark: &str,
     ) -> Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue>;
+
+    #[cfg(feature = "profile")]
+    #[wasm_bindgen(catch, js_namespace = performance, js_name = "clearMarks")]
+    pub fn performance_clear_marks(name: &str) -> Result<(), wasm_bindgen::JsValue>;
+
+    #[cfg(feature = "tokio")]
+    #[wasm_bindgen(structural, method, getter, js_name = "setTimeout")]
+    pub fn set_timeout_function(this: &GlobalScope) -> Function;
+
+    #[cfg(feature = "tokio")]
+    #[wasm_bindgen(structural, method, getter, js_name = "clearTimeout")]
+    pub fn clear_timeout_function(this: &GlobalScope) -> Function;
+}
+
+// Alternatives to `setTimeout` for the timer driver, see `timer::scheduler`.
+// Whether they exist is checked before they are used.
+#[cfg(feature = "tokio")]
+#[wasm_bindgen]
+extern "C" {
+    pub type MessageChannel;
+
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> MessageChannel;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn port1(this: &MessageChannel) -> MessagePort;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn port2(this: &MessageChannel) -> MessagePort;
+
+    #[derive(Clone)]
+    pub type MessagePort;
+
+    #[wasm_bindgen(method, setter)]
+    pub fn set_onmessage(this: &MessagePort, handler: &Function);
+
+    #[wasm_bindgen(method, js_name = "postMessage")]
+    pub fn post_message(this: &MessagePort, message: &wasm_bindgen::JsValue);
+
+    #[wasm_bindgen(js_name = "queueMicrotask")]
+    pub fn queue_microtask(callback: &Function);
+
+    #[wasm_bindgen(js_namespace = scheduler, js_name = "postTask")]
+    pub fn post_task(callback: &Function, options: &js_sys::Object) -> js_sys::Promise;
 }
 
-pub fn performance_now() -> f64 {
-    let global_this: Object = js_sys::global();
-    let global_scope = global_this.unchecked_ref::<GlobalScope>();
-    global_scope.performance().now()
+#[cfg(feature = "intl")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Intl)]
+    pub type DateTimeFormat;
+
+    #[wasm_bindgen(catch, constructor, js_namespace = Intl)]
+    pub fn new(
+        locales: &wasm_bindgen::JsValue,
+        options: &js_sys::Object,
+    ) -> Result<DateTimeFormat, wasm_bindgen::JsValue>;
+
+    #[wasm_bindgen(catch, method, js_namespace = Intl)]
+    pub fn format(this: &DateTimeFormat, millis: f64) -> Result<String, wasm_bindgen::JsValue>;
+
+    #[wasm_bindgen(js_namespace = Intl)]
+    pub type RelativeTimeFormat;
+
+    #[wasm_bindgen(catch, constructor, js_namespace = Intl)]
+    pub fn new(
+        locales: &wasm_bindgen::JsValue,
+        options: &js_sys::Object,
+    ) -> Result<RelativeTimeFormat, wasm_bindgen::JsValue>;
+
+    #[wasm_bindgen(method, js_namespace = Intl)]
+    pub fn format(this: &RelativeTimeFormat, value: f64, unit: &str) -> String;
+}
+
+// Looking these up crosses the JS boundary, so they are only looked up once
+// per thread.
+thread_local! {
+    static GLOBAL_SCOPE: GlobalScope = js_sys::global().unchecked_into();
+    static PERFORMANCE: Performance = GLOBAL_SCOPE.with(GlobalScope::performance);
+    #[cfg(feature = "tokio")]
+    static SET_TIMEOUT: Function = GLOBAL_SCOPE.with(GlobalScope::set_timeout_function);
+    #[cfg(feature = "tokio")]
+    static CLEAR_TIMEOUT: Function = GLOBAL_SCOPE.with(GlobalScope::clear_timeout_function);
+}
+
+pub fn performance_time_origin() -> f64 {
+    PERFORMANCE.with(Performance::time_origin)
 }
 
 #[cfg(feature = "tokio")]
@@ -33,7 +123,16 @@ pub fn set_timeout(
     handler: &::js_sys::Function,
     timeout: i32,
 ) -> Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue> {
-    let global_this: Object = js_sys::global();
-    let global_scope = global_this.unchecked_ref::<GlobalScope>();
-    global_scope.set_timeout_with_callback_and_timeout_and_arguments_0(handler, timeout)
+    GLOBAL_SCOPE.with(|global_scope| {
+        SET_TIMEOUT.with(|set_timeout| {
+            set_timeout.call2(global_scope, handler, &wasm_bindgen::JsValue::from(timeout))
+        })
+    })
+}
+
+#[cfg(feature = "tokio")]
+pub fn clear_timeout(id: &wasm_bindgen::JsValue) -> Result<(), wasm_bindgen::JsValue> {
+    GLOBAL_SCOPE.with(|global_scope| {
+        CLEAR_TIMEOUT.with(|clear_timeout| clear_timeout.call1(global_scope, id).map(drop))
+    })
 }
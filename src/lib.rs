@@ -17,11 +17,36 @@
 // LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
+
+// With the `native-fallback` feature, non-WASM targets re-export the original
+// `std::time`, `tokio::time` and `tokio_util::time` items instead. WASI
+// targets have no JS global object and read the time through WASI instead.
+#[cfg(all(
+    not(target_os = "wasi"),
+    any(target_family = "wasm", not(feature = "native-fallback"))
+))]
 mod js;
+// Available everywhere, as it does nothing without the `profile` feature.
+pub mod profile;
+#[cfg(any(target_family = "wasm", not(feature = "native-fallback")))]
 pub mod std;
-#[cfg(feature = "tokio")]
+#[cfg(all(
+    feature = "tokio",
+    any(target_family = "wasm", not(feature = "native-fallback"))
+))]
 pub(crate) mod timer;
-#[cfg(feature = "tokio")]
+#[cfg(all(
+    feature = "tokio",
+    any(target_family = "wasm", not(feature = "native-fallback"))
+))]
 pub mod tokio;
-#[cfg(feature = "tokio-util")]
+#[cfg(all(
+    feature = "tokio-util",
+    any(target_family = "wasm", not(feature = "native-fallback"))
+))]
 pub mod tokio_util;
+
+#[cfg(all(not(target_family = "wasm"), feature = "native-fallback"))]
+mod native;
+#[cfg(all(not(target_family = "wasm"), feature = "native-fallback"))]
+pub use native::*;
@@ -0,0 +1,95 @@
+//! Re-exports of the native implementations, used on non-WASM targets when the
+//! `native-fallback` feature is enabled.
+//!
+//! Only the items mirrored from `std::time`, `tokio::time` and
+//! `tokio_util::time` are available here. The `tokio` and `tokio_util` items
+//! need a running tokio runtime with the time driver enabled, as usual.
+
+pub mod std {
+    pub use ::std::time::{SystemTime, SystemTimeError, UNIX_EPOCH};
+
+    /// `tokio::time::Instant` is used when the `tokio` feature is enabled, so
+    /// that `Instant::now` follows the paused clock of `tokio-test-util` like
+    /// it does on WASM targets, and so that instants can be passed to
+    /// functions such as `sleep_until`.
+    #[cfg(feature = "tokio")]
+    pub use tokio_crate::time::Instant;
+
+    #[cfg(not(feature = "tokio"))]
+    pub use ::std::time::Instant;
+
+    pub mod thread {
+        pub use ::std::thread::sleep;
+    }
+
+    /// Returns the signed number of nanoseconds from `UNIX_EPOCH` to `time`.
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    fn unix_timestamp_nanos(time: SystemTime) -> i128 {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since_epoch) => since_epoch.as_nanos() as i128,
+            Err(err) => -(err.duration().as_nanos() as i128),
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    pub mod chrono {
+        use chrono_crate::{DateTime, Utc};
+
+        /// Returns the current time as a `DateTime<Utc>`.
+        pub fn now_utc() -> DateTime<Utc> {
+            let nanos = super::unix_timestamp_nanos(super::SystemTime::now());
+            i64::try_from(nanos.div_euclid(1_000_000_000))
+                .ok()
+                .and_then(|secs| {
+                    DateTime::from_timestamp(secs, nanos.rem_euclid(1_000_000_000) as u32)
+                })
+                .expect("current time is out of range for chrono")
+        }
+    }
+
+    #[cfg(feature = "time")]
+    pub mod time {
+        use time_crate::OffsetDateTime;
+
+        /// Returns the current time as an `OffsetDateTime` in UTC.
+        pub fn now_utc() -> OffsetDateTime {
+            let nanos = super::unix_timestamp_nanos(super::SystemTime::now());
+            OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                .expect("current time is out of range for time")
+        }
+    }
+
+    #[cfg(all(test, any(feature = "chrono", feature = "time")))]
+    mod tests {
+        use super::*;
+        use ::std::time::Duration;
+
+        #[test]
+        fn unix_timestamp_nanos_before_epoch() {
+            assert_eq!(
+                unix_timestamp_nanos(UNIX_EPOCH + Duration::new(1, 500)),
+                1_000_000_500
+            );
+            assert_eq!(
+                unix_timestamp_nanos(UNIX_EPOCH - Duration::new(1, 500)),
+                -1_000_000_500
+            );
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub mod tokio {
+    pub use tokio_crate::time::{
+        error, interval, interval_at, sleep, sleep_until, timeout, timeout_at, Interval,
+        MissedTickBehavior, Sleep, Timeout,
+    };
+
+    #[cfg(feature = "tokio-test-util")]
+    pub use tokio_crate::time::{advance, pause, resume};
+}
+
+#[cfg(feature = "tokio-util")]
+pub mod tokio_util {
+    pub use tokio_util_crate::time::{delay_queue, DelayQueue};
+}
@@ -0,0 +1,158 @@
+//! Timing of code sections in the Performance panel of the browser DevTools.
+//!
+//! A [`Span`] adds a `performance.mark` when it is created and a
+//! `performance.measure` covering its lifetime when it is dropped. Futures
+//! can be timed from their first poll until they complete with
+//! [`ProfileFutureExt::profiled`].
+//!
+//! Without the `profile` feature, and on hosts without JS, everything in here
+//! does nothing, so the instrumentation can stay in the code.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(all(
+    feature = "profile",
+    not(target_os = "wasi"),
+    any(target_family = "wasm", not(feature = "native-fallback"))
+))]
+mod imp {
+    use std::borrow::Cow;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use crate::js::{performance_clear_marks, performance_mark, performance_measure};
+
+    /// Makes the start marks of spans with the same name unique, so that
+    /// nested or concurrent spans measure from their own start.
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    pub(super) struct Span {
+        name: Cow<'static, str>,
+        start_mark: String,
+    }
+
+    impl Span {
+        pub(super) fn new(name: Cow<'static, str>) -> Span {
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            let start_mark = format!("{name} #{id}");
+            // Profiling must not break the program, so errors from the host,
+            // such as for reserved names, are ignored.
+            let _ = performance_mark(&start_mark);
+            Span { name, start_mark }
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            let _ = performance_measure(&self.name, &self.start_mark);
+            let _ = performance_clear_marks(&self.start_mark);
+        }
+    }
+}
+
+#[cfg(not(all(
+    feature = "profile",
+    not(target_os = "wasi"),
+    any(target_family = "wasm", not(feature = "native-fallback"))
+)))]
+mod imp {
+    use std::borrow::Cow;
+
+    pub(super) struct Span;
+
+    impl Span {
+        #[inline(always)]
+        pub(super) fn new(_name: Cow<'static, str>) -> Span {
+            Span
+        }
+    }
+}
+
+/// A timed section of code, shown as a measure named after the span in the
+/// Performance panel.
+///
+/// The measure ends when the span is dropped.
+#[must_use = "the span ends as soon as it is dropped"]
+pub struct Span {
+    _inner: imp::Span,
+}
+
+impl Span {
+    /// Starts a span named `name`.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Span {
+        Span {
+            _inner: imp::Span::new(name.into()),
+        }
+    }
+}
+
+impl std::fmt::Debug for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Span").finish_non_exhaustive()
+    }
+}
+
+/// Future returned by [`ProfileFutureExt::profiled`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Profiled<F> {
+    future: F,
+    /// Taken when the span starts on the first poll.
+    name: Option<Cow<'static, str>>,
+    span: Option<Span>,
+}
+
+impl<F: Future> Future for Profiled<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // Safety: `future` is structurally pinned. It is never moved out of
+        // `self` while pinned and `Profiled` does not implement `Drop`.
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Some(name) = this.name.take() {
+            this.span = Some(Span::new(name));
+        }
+        let future = unsafe { Pin::new_unchecked(&mut this.future) };
+        match future.poll(cx) {
+            Poll::Ready(output) => {
+                this.span = None;
+                Poll::Ready(output)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Extension trait to time futures with a [`Span`].
+pub trait ProfileFutureExt: Future + Sized {
+    /// Times this future with a span named `name`, from its first poll until
+    /// it completes or is dropped.
+    ///
+    /// The span includes the time the future spends waiting between polls.
+    fn profiled(self, name: impl Into<Cow<'static, str>>) -> Profiled<Self> {
+        Profiled {
+            future: self,
+            name: Some(name.into()),
+            span: None,
+        }
+    }
+}
+
+impl<F: Future> ProfileFutureExt for F {}
+
+#[cfg(all(test, not(feature = "profile")))]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+
+    #[test]
+    fn disabled() {
+        assert_eq!(std::mem::size_of::<Span>(), 0);
+        let _span = Span::new("disabled");
+
+        let mut future = std::pin::pin!(std::future::ready(42).profiled("ready"));
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(future.as_mut().poll(&mut cx), Poll::Ready(42));
+    }
+}
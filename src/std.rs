@@ -19,19 +19,47 @@
 // FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 
-use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+use std::cell::Cell;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 use std::time::Duration;
 
-use crate::js::performance_now;
+mod calendar;
+#[cfg(feature = "chrono")]
+pub mod chrono;
+mod clock;
+mod format;
+mod interop;
+#[cfg(all(feature = "intl", not(target_os = "wasi")))]
+pub mod intl;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod thread;
+#[cfg(feature = "time")]
+pub mod time;
+pub use calendar::DateTimeParts;
+#[cfg(not(target_os = "wasi"))]
+pub use clock::JsClockSource;
+pub use clock::{set_clock_source, ClockSource, SetClockSourceError, StdClockSource};
+pub use format::ParseError;
+pub use interop::{duration_from_js_millis, duration_to_js_millis, JsTimeError};
 
 #[derive(Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, Debug)]
 pub struct Instant(Duration);
 
+// Each worker has its own `performance.timeOrigin`, so instants are only
+// comparable within a thread.
+thread_local! {
+    /// The latest value returned by `Instant::now_unmocked`, in nanoseconds.
+    static LAST_NOW: Cell<u64> = const { Cell::new(0) };
+    /// How many times `Instant::now_unmocked` had to clamp a value going
+    /// backwards.
+    static CLAMP_COUNT: Cell<u64> = const { Cell::new(0) };
+}
+
 impl Instant {
     #[cfg(not(all(feature = "tokio-test-util", feature = "tokio")))]
     pub fn now() -> Instant {
-        Self::now_js()
+        Self::now_unmocked()
     }
 
     #[cfg(all(feature = "tokio-test-util", feature = "tokio"))]
@@ -39,11 +67,73 @@ impl Instant {
         crate::timer::clock::now()
     }
 
-    pub(crate) fn now_js() -> Instant {
-        let val = (performance_now() * 1000.0) as u64;
+    /// Reads the installed [`ClockSource`], ignoring the mocked clock of
+    /// `tokio-test-util`.
+    pub(crate) fn now_unmocked() -> Instant {
+        Instant::clamp_monotonic(Instant(clock::clock_source().monotonic_now()))
+    }
+
+    /// Makes sure that `now` is not earlier than any instant returned before
+    /// on the current thread.
+    ///
+    /// `performance.now()` is specified to be monotonic, but some embedded
+    /// runtimes, coarsened clocks and custom clock sources briefly go
+    /// backwards. Such values are
+    /// replaced by the latest value returned so far.
+    fn clamp_monotonic(now: Instant) -> Instant {
+        let nanos = u64::try_from(now.0.as_nanos()).unwrap_or(u64::MAX);
+        let last = LAST_NOW.with(|last_now| last_now.replace(last_now.get().max(nanos)));
+        if last > nanos {
+            CLAMP_COUNT.with(|count| count.set(count.get() + 1));
+            Instant(Duration::from_nanos(last))
+        } else {
+            now
+        }
+    }
+
+    /// Returns how many times the clock went backwards on the current thread
+    /// and [`Instant::now`] returned the previous value instead.
+    ///
+    /// A non-zero value hints at a host whose `performance.now()` is not
+    /// monotonic.
+    pub fn monotonic_clamp_count() -> u64 {
+        CLAMP_COUNT.with(Cell::get)
+    }
+
+    /// Creates an `Instant` from a `DOMHighResTimeStamp`, rounding to the
+    /// nearest microsecond.
+    ///
+    /// High resolution timestamps are milliseconds relative to
+    /// `performance.timeOrigin`, as reported by `performance.now()`,
+    /// `Event.timeStamp`, `requestAnimationFrame` callbacks and
+    /// `PerformanceEntry.startTime`. The result can be compared with
+    /// [`Instant::now`], e.g. to measure input latency.
+    ///
+    /// Negative timestamps and `NaN` saturate to the time origin.
+    pub fn from_high_res_timestamp(timestamp: f64) -> Instant {
+        let val = (timestamp * 1000.0).round() as u64;
         Instant(Duration::from_micros(val))
     }
 
+    #[cfg(not(target_os = "wasi"))]
+    /// Like [`Instant::from_high_res_timestamp`], but rejects values that do
+    /// not describe an instant instead of saturating them.
+    pub(crate) fn try_from_high_res_timestamp(timestamp: f64) -> Result<Instant, JsTimeError> {
+        if !timestamp.is_finite() {
+            return Err(JsTimeError::new("time value is not a finite number"));
+        }
+        if timestamp < 0.0 || timestamp * 1000.0 >= u64::MAX as f64 {
+            return Err(JsTimeError::new("time value is out of range for Instant"));
+        }
+        Ok(Instant::from_high_res_timestamp(timestamp))
+    }
+
+    /// Returns this instant as a `DOMHighResTimeStamp`, the number of
+    /// milliseconds since `performance.timeOrigin`.
+    pub fn as_high_res_timestamp(&self) -> f64 {
+        self.0.as_nanos() as f64 / NANOS_PER_MILLI
+    }
+
     pub fn duration_since(&self, earlier: Instant) -> Duration {
         self.checked_duration_since(earlier).unwrap_or_default()
     }
@@ -67,6 +157,37 @@ impl Instant {
     pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
         self.0.checked_sub(duration).map(Instant)
     }
+
+    /// Returns an instant roughly 30 years from now.
+    ///
+    /// Timers use it in place of deadlines that cannot be represented, such
+    /// as `Instant::now() + Duration::MAX`, like tokio does. It is useful as
+    /// a deadline that is never reached in practice, e.g. to disable a
+    /// `Sleep` until it is reset.
+    pub fn far_future() -> Instant {
+        Instant::now() + Duration::from_secs(86400 * 365 * 30)
+    }
+
+    /// Returns the wall-clock time at which this instant occurred.
+    ///
+    /// Instants count from `performance.timeOrigin`, the wall-clock time at
+    /// which the current JS context (page or worker) started, so the result is
+    /// `performance.timeOrigin` plus the offset of this instant.
+    ///
+    /// The monotonic clock behind [`Instant`] does not follow adjustments of the
+    /// system clock and may not advance while the host is suspended. As a
+    /// result, `Instant::now().to_system_time()` drifts away from
+    /// [`SystemTime::now`] the longer the context runs. Use `SystemTime::now`
+    /// when the current wall-clock time is needed.
+    pub fn to_system_time(&self) -> SystemTime {
+        time_origin() + self.0
+    }
+}
+
+impl From<Instant> for SystemTime {
+    fn from(instant: Instant) -> SystemTime {
+        instant.to_system_time()
+    }
 }
 
 impl Add<Duration> for Instant {
@@ -121,12 +242,54 @@ impl Sub<Instant> for Instant {
     }
 }
 
-pub const UNIX_EPOCH: SystemTime = SystemTime { inner: 0.0 };
+/// Serializes as the wall-clock time of the instant, using the
+/// [`SystemTime`] encoding.
+///
+/// An `Instant` only has meaning within the JS context that created it, as it
+/// counts from that context's `performance.timeOrigin`. Adding the time origin
+/// on serialization and subtracting the receiving context's time origin on
+/// deserialization keeps values meaningful across workers and page loads.
+/// Instants that are earlier than the receiving context's time origin
+/// saturate to the time origin.
+#[cfg(feature = "serde")]
+impl serde_crate::ser::Serialize for Instant {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde_crate::ser::Serializer,
+    {
+        self.to_system_time().serialize(serializer)
+    }
+}
 
-#[derive(Debug, Copy, Clone)]
+#[cfg(feature = "serde")]
+impl<'de> serde_crate::de::Deserialize<'de> for Instant {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde_crate::de::Deserializer<'de>,
+    {
+        SystemTime::deserialize(deserializer).map(|system_time| system_time.to_instant())
+    }
+}
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+const NANOS_PER_MILLI: f64 = 1_000_000.0;
+const MILLIS_PER_SEC: f64 = 1_000.0;
+
+pub const UNIX_EPOCH: SystemTime = SystemTime { secs: 0, nanos: 0 };
+
+/// A measurement of the system clock.
+///
+/// The value is stored as a signed number of whole seconds relative to
+/// [`UNIX_EPOCH`] plus a nanosecond fraction, the same layout used by the
+/// native `std::time::SystemTime`. Arithmetic and comparisons are exact down
+/// to the nanosecond, and `SystemTime` is totally ordered and can be used as a
+/// `HashMap` key.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SystemTime {
-    /// Unit is milliseconds.
-    inner: f64,
+    /// Whole seconds since `UNIX_EPOCH`, rounded towards negative infinity.
+    secs: i64,
+    /// Always in the range `0..NANOS_PER_SEC`.
+    nanos: u32,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -144,63 +307,152 @@ impl std::fmt::Display for SystemTimeError {
     }
 }
 
-impl PartialEq for SystemTime {
-    fn eq(&self, other: &SystemTime) -> bool {
-        // Note that this will most likely only compare equal if we clone an `SystemTime`,
-        // but that's ok.
-        self.inner == other.inner
-    }
-}
-
-impl Eq for SystemTime {}
+/// Error returned when a `SystemTime` does not fit in the date-time type of
+/// another crate, or can not be represented in a format such as HTTP dates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfRangeError(());
 
-impl PartialOrd for SystemTime {
-    fn partial_cmp(&self, other: &SystemTime) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl std::fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "system time is out of range for the target type")
     }
 }
 
-impl Ord for SystemTime {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.inner.partial_cmp(&other.inner).unwrap()
-    }
-}
+impl std::error::Error for OutOfRangeError {}
 
 impl SystemTime {
-    pub const UNIX_EPOCH: SystemTime = SystemTime { inner: 0.0 };
+    pub const UNIX_EPOCH: SystemTime = UNIX_EPOCH;
 
+    /// # Panics
+    ///
+    /// Panics if `Date.now()` returns `NaN`, an infinity or a value that is
+    /// out of range, e.g. because it was replaced by a broken polyfill.
     pub fn now() -> SystemTime {
-        let val = js_sys::Date::now();
-        SystemTime { inner: val }
+        clock::clock_source().system_now()
     }
 
-    pub fn duration_since(&self, earlier: SystemTime) -> Result<Duration, SystemTimeError> {
-        let dur_ms = self.inner - earlier.inner;
-        if dur_ms < 0.0 {
-            return Err(SystemTimeError(Duration::from_millis(dur_ms.abs() as u64)));
+    #[cfg(any(not(target_os = "wasi"), feature = "serde"))]
+    /// Converts a JS timestamp (milliseconds since `UNIX_EPOCH`, as returned
+    /// by `Date.now()`) to a `SystemTime`, rounding to the nearest nanosecond.
+    ///
+    /// `NaN`, infinities and values that are out of range are rejected, so
+    /// that every `SystemTime` is a valid point in time.
+    pub(crate) fn try_from_js_millis(millis: f64) -> Result<SystemTime, JsTimeError> {
+        if !millis.is_finite() {
+            return Err(JsTimeError::new("time value is not a finite number"));
+        }
+        let secs = (millis / MILLIS_PER_SEC).floor();
+        if secs < i64::MIN as f64 || secs >= i64::MAX as f64 {
+            return Err(JsTimeError::new(
+                "time value is out of range for SystemTime",
+            ));
         }
-        Ok(Duration::from_millis(dur_ms as u64))
+        let nanos = ((millis - secs * MILLIS_PER_SEC) * NANOS_PER_MILLI).round() as u32;
+        let secs = secs as i64;
+        if nanos >= NANOS_PER_SEC {
+            Ok(SystemTime {
+                secs: secs + 1,
+                nanos: nanos - NANOS_PER_SEC,
+            })
+        } else {
+            Ok(SystemTime { secs, nanos })
+        }
+    }
+
+    #[cfg(not(target_os = "wasi"))]
+    /// Returns the number of milliseconds since `UNIX_EPOCH`, as used by JS
+    /// `Date` objects.
+    pub(crate) fn to_js_millis(self) -> f64 {
+        self.secs as f64 * MILLIS_PER_SEC + f64::from(self.nanos) / NANOS_PER_MILLI
+    }
+
+    pub fn duration_since(&self, earlier: SystemTime) -> Result<Duration, SystemTimeError> {
+        self.sub_time(&earlier).map_err(SystemTimeError)
     }
 
     pub fn elapsed(&self) -> Result<Duration, SystemTimeError> {
         SystemTime::now().duration_since(*self)
     }
 
+    /// Returns the [`Instant`] corresponding to this wall-clock time.
+    ///
+    /// This is the inverse of [`Instant::to_system_time`] and is subject to
+    /// the same drift between the two clocks. Times earlier than
+    /// `performance.timeOrigin` cannot be represented by an `Instant` and
+    /// saturate to the time origin.
+    pub fn to_instant(&self) -> Instant {
+        Instant(self.duration_since(time_origin()).unwrap_or_default())
+    }
+
     pub fn checked_add(&self, duration: Duration) -> Option<SystemTime> {
-        Some(*self + duration)
+        let mut secs = self.secs.checked_add_unsigned(duration.as_secs())?;
+        let mut nanos = self.nanos + duration.subsec_nanos();
+        if nanos >= NANOS_PER_SEC {
+            nanos -= NANOS_PER_SEC;
+            secs = secs.checked_add(1)?;
+        }
+        Some(SystemTime { secs, nanos })
     }
 
     pub fn checked_sub(&self, duration: Duration) -> Option<SystemTime> {
-        Some(*self - duration)
+        let mut secs = self.secs.checked_sub_unsigned(duration.as_secs())?;
+        let nanos = if self.nanos >= duration.subsec_nanos() {
+            self.nanos - duration.subsec_nanos()
+        } else {
+            secs = secs.checked_sub(1)?;
+            self.nanos + NANOS_PER_SEC - duration.subsec_nanos()
+        };
+        Some(SystemTime { secs, nanos })
     }
+
+    /// Returns `Ok(self - other)` if `self` is not earlier than `other`,
+    /// otherwise `Err(other - self)`.
+    fn sub_time(&self, other: &SystemTime) -> Result<Duration, Duration> {
+        if self >= other {
+            // The difference of two `i64`s always fits in a `u64`, so the
+            // wrapping operations below yield the exact result.
+            let (secs, nanos) = if self.nanos >= other.nanos {
+                (
+                    self.secs.wrapping_sub(other.secs) as u64,
+                    self.nanos - other.nanos,
+                )
+            } else {
+                (
+                    (self.secs.wrapping_sub(other.secs) as u64).wrapping_sub(1),
+                    self.nanos + NANOS_PER_SEC - other.nanos,
+                )
+            };
+            Ok(Duration::new(secs, nanos))
+        } else {
+            match other.sub_time(self) {
+                Ok(d) => Err(d),
+                Err(d) => Ok(d),
+            }
+        }
+    }
+}
+
+impl From<SystemTime> for Instant {
+    fn from(system_time: SystemTime) -> Instant {
+        system_time.to_instant()
+    }
+}
+
+/// Returns `performance.timeOrigin` as a `SystemTime`.
+fn time_origin() -> SystemTime {
+    clock::clock_source().time_origin()
 }
 
 impl Add<Duration> for SystemTime {
     type Output = SystemTime;
 
+    /// # Panics
+    ///
+    /// This function may panic if the resulting point in time cannot be represented by the
+    /// underlying data structure. See [`SystemTime::checked_add`] for a version without panic.
     fn add(self, other: Duration) -> SystemTime {
-        let new_val = self.inner + other.as_millis() as f64;
-        SystemTime { inner: new_val }
+        self.checked_add(other)
+            .expect("overflow when adding duration to instant")
     }
 }
 
@@ -208,8 +460,8 @@ impl Sub<Duration> for SystemTime {
     type Output = SystemTime;
 
     fn sub(self, other: Duration) -> SystemTime {
-        let new_val = self.inner - other.as_millis() as f64;
-        SystemTime { inner: new_val }
+        self.checked_sub(other)
+            .expect("overflow when subtracting duration from instant")
     }
 }
 
@@ -225,20 +477,31 @@ impl SubAssign<Duration> for SystemTime {
     }
 }
 
+/// Serializes as `{ secs_since_epoch, nanos_since_epoch }`, the same encoding
+/// used by `serde` for `std::time::SystemTime`.
+///
+/// Times before `UNIX_EPOCH` are written with a negative `secs_since_epoch`
+/// and a non-negative `nanos_since_epoch` to add to it, so half a second
+/// before the epoch is `{ -1, 500_000_000 }`. Seconds are always written as
+/// a `u64`, like `std` does, so that formats which are not self-describing
+/// read them back with the same type; negative seconds are written as their
+/// two's complement. Human-readable formats write negative seconds as an
+/// `i64` instead.
 #[cfg(feature = "serde")]
 impl serde_crate::ser::Serialize for SystemTime {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde_crate::ser::Serializer,
     {
-        use serde_crate::ser::{Error, SerializeStruct};
-        let duration_since_epoch = match self.duration_since(UNIX_EPOCH) {
-            Ok(duration_since_epoch) => duration_since_epoch,
-            Err(_) => return Err(S::Error::custom("SystemTime must be later than UNIX_EPOCH")),
-        };
+        use serde_crate::ser::SerializeStruct;
+        let serializer_is_human_readable = serializer.is_human_readable();
         let mut state = serializer.serialize_struct("SystemTime", 2)?;
-        state.serialize_field("secs_since_epoch", &duration_since_epoch.as_secs())?;
-        state.serialize_field("nanos_since_epoch", &duration_since_epoch.subsec_nanos())?;
+        if self.secs < 0 && serializer_is_human_readable {
+            state.serialize_field("secs_since_epoch", &self.secs)?;
+        } else {
+            state.serialize_field("secs_since_epoch", &(self.secs as u64))?;
+        }
+        state.serialize_field("nanos_since_epoch", &self.nanos)?;
         state.end()
     }
 }
@@ -301,21 +564,66 @@ impl<'de> serde_crate::de::Deserialize<'de> for SystemTime {
             }
         }
 
-        fn check_overflow<E>(secs: u64, nanos: u32) -> Result<(), E>
+        // Seconds are signed, but written as a `u64`. Accept both signed and
+        // unsigned integers in human-readable formats, where negative seconds
+        // are written as an `i64`, and read them back as two's complement in
+        // the others.
+        struct EpochSecs(i64);
+
+        impl<'de> Deserialize<'de> for EpochSecs {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                struct EpochSecsVisitor {
+                    is_human_readable: bool,
+                }
+
+                impl<'de> Visitor<'de> for EpochSecsVisitor {
+                    type Value = EpochSecs;
+
+                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                        formatter.write_str("seconds since UNIX_EPOCH")
+                    }
+
+                    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+                    where
+                        E: Error,
+                    {
+                        Ok(EpochSecs(value))
+                    }
+
+                    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+                    where
+                        E: Error,
+                    {
+                        if !self.is_human_readable {
+                            return Ok(EpochSecs(value as i64));
+                        }
+                        i64::try_from(value).map(EpochSecs).map_err(|_| {
+                            E::custom("overflow deserializing SystemTime epoch offset")
+                        })
+                    }
+                }
+
+                let is_human_readable = deserializer.is_human_readable();
+                deserializer.deserialize_u64(EpochSecsVisitor { is_human_readable })
+            }
+        }
+
+        fn from_parts<E>(secs: i64, nanos: u32) -> Result<SystemTime, E>
         where
             E: Error,
         {
-            static NANOS_PER_SEC: u32 = 1_000_000_000;
-            match secs.checked_add((nanos / NANOS_PER_SEC) as u64) {
-                Some(_) => Ok(()),
-                None => Err(E::custom("overflow deserializing SystemTime epoch offset")),
-            }
+            SystemTime { secs, nanos: 0 }
+                .checked_add(Duration::from_nanos(nanos.into()))
+                .ok_or_else(|| E::custom("overflow deserializing SystemTime"))
         }
 
-        struct DurationVisitor;
+        struct SystemTimeVisitor;
 
-        impl<'de> Visitor<'de> for DurationVisitor {
-            type Value = Duration;
+        impl<'de> Visitor<'de> for SystemTimeVisitor {
+            type Value = SystemTime;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("struct SystemTime")
@@ -325,7 +633,7 @@ impl<'de> serde_crate::de::Deserialize<'de> for SystemTime {
             where
                 A: SeqAccess<'de>,
             {
-                let secs: u64 = match seq.next_element()? {
+                let secs: EpochSecs = match seq.next_element()? {
                     Some(value) => value,
                     None => {
                         return Err(Error::invalid_length(0, &self));
@@ -337,15 +645,14 @@ impl<'de> serde_crate::de::Deserialize<'de> for SystemTime {
                         return Err(Error::invalid_length(1, &self));
                     }
                 };
-                check_overflow(secs, nanos)?;
-                Ok(Duration::new(secs, nanos))
+                from_parts(secs.0, nanos)
             }
 
             fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
             where
                 A: MapAccess<'de>,
             {
-                let mut secs: Option<u64> = None;
+                let mut secs: Option<EpochSecs> = None;
                 let mut nanos: Option<u32> = None;
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -375,15 +682,344 @@ impl<'de> serde_crate::de::Deserialize<'de> for SystemTime {
                     Some(nanos) => nanos,
                     None => return Err(<A::Error as Error>::missing_field("nanos_since_epoch")),
                 };
-                check_overflow(secs, nanos)?;
-                Ok(Duration::new(secs, nanos))
+                from_parts(secs.0, nanos)
             }
         }
 
         const FIELDS: &[&str] = &["secs_since_epoch", "nanos_since_epoch"];
-        let duration = deserializer.deserialize_struct("SystemTime", FIELDS, DurationVisitor)?;
-        UNIX_EPOCH
-            .checked_add(duration)
-            .ok_or_else(|| D::Error::custom("overflow deserializing SystemTime"))
+        deserializer.deserialize_struct("SystemTime", FIELDS, SystemTimeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sub_millisecond_arithmetic() {
+        let t = UNIX_EPOCH + Duration::new(1, 500);
+        assert_eq!(t.duration_since(UNIX_EPOCH).unwrap(), Duration::new(1, 500));
+        assert_eq!(
+            t - Duration::from_nanos(500),
+            UNIX_EPOCH + Duration::from_secs(1)
+        );
+        assert!(t > UNIX_EPOCH + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn duration_since_later() {
+        let a = UNIX_EPOCH + Duration::new(10, 900_000_000);
+        let b = UNIX_EPOCH + Duration::new(12, 100_000_000);
+        assert_eq!(b.duration_since(a).unwrap(), Duration::new(1, 200_000_000));
+        assert_eq!(
+            a.duration_since(b).unwrap_err().duration(),
+            Duration::new(1, 200_000_000)
+        );
+    }
+
+    #[test]
+    fn nanos_carry() {
+        let t = UNIX_EPOCH + Duration::new(0, 999_999_999) + Duration::from_nanos(2);
+        assert_eq!(t.duration_since(UNIX_EPOCH).unwrap(), Duration::new(1, 1));
+        let t = t - Duration::from_nanos(3);
+        assert_eq!(
+            t.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::new(0, 999_999_998)
+        );
+    }
+
+    #[cfg(not(target_os = "wasi"))]
+    #[test]
+    fn from_js_millis() {
+        let t = SystemTime::try_from_js_millis(1_700_000_000_123.0).unwrap();
+        assert_eq!(
+            t.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_millis(1_700_000_000_123)
+        );
+        let t = SystemTime::try_from_js_millis(0.25).unwrap();
+        assert_eq!(
+            t.duration_since(UNIX_EPOCH).unwrap(),
+            Duration::from_micros(250)
+        );
+    }
+
+    #[test]
+    fn high_res_timestamp() {
+        let instant = Instant::from_high_res_timestamp(1234.5678);
+        assert_eq!(instant.as_high_res_timestamp(), 1234.568);
+        assert_eq!(
+            instant - Instant::from_high_res_timestamp(1000.0),
+            Duration::from_micros(234_568)
+        );
+        assert_eq!(
+            Instant::from_high_res_timestamp(-5.0),
+            Instant::from_high_res_timestamp(0.0)
+        );
+        assert_eq!(
+            Instant::from_high_res_timestamp(f64::NAN),
+            Instant::from_high_res_timestamp(0.0)
+        );
+    }
+
+    #[test]
+    fn clamp_monotonic() {
+        // The clamp state is per thread, and each test runs on its own
+        // thread, so instants read by other tests do not interfere.
+        let count = Instant::monotonic_clamp_count();
+        let later = Instant::from_high_res_timestamp(2000.0);
+        let earlier = Instant::from_high_res_timestamp(1000.0);
+        assert_eq!(Instant::clamp_monotonic(later), later);
+        assert_eq!(Instant::clamp_monotonic(earlier), later);
+        assert_eq!(Instant::monotonic_clamp_count(), count + 1);
+        let latest = Instant::from_high_res_timestamp(3000.0);
+        assert_eq!(Instant::clamp_monotonic(latest), latest);
+        assert_eq!(Instant::monotonic_clamp_count(), count + 1);
+    }
+
+    #[test]
+    fn checked_overflow() {
+        let max = UNIX_EPOCH + Duration::new(i64::MAX as u64, NANOS_PER_SEC - 1);
+        assert_eq!(max.checked_add(Duration::from_nanos(1)), None);
+        assert_eq!(UNIX_EPOCH.checked_add(Duration::MAX), None);
+    }
+
+    #[test]
+    fn before_epoch() {
+        let t = UNIX_EPOCH - Duration::from_millis(500);
+        assert_eq!(
+            t,
+            SystemTime {
+                secs: -1,
+                nanos: 500_000_000
+            }
+        );
+        assert!(t < UNIX_EPOCH);
+        assert_eq!(
+            UNIX_EPOCH.duration_since(t).unwrap(),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            t.duration_since(UNIX_EPOCH).unwrap_err().duration(),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            t + Duration::from_millis(1500),
+            UNIX_EPOCH + Duration::from_secs(1)
+        );
+
+        // 1969-07-20T20:17:40Z
+        let moon_landing = SystemTime {
+            secs: -14_182_940,
+            nanos: 0,
+        };
+        assert_eq!(
+            UNIX_EPOCH.duration_since(moon_landing).unwrap(),
+            Duration::from_secs(14_182_940)
+        );
+    }
+
+    #[test]
+    fn checked_sub_overflow() {
+        let min = SystemTime {
+            secs: i64::MIN,
+            nanos: 0,
+        };
+        assert_eq!(min.checked_sub(Duration::from_nanos(1)), None);
+        assert_eq!(UNIX_EPOCH.checked_sub(Duration::MAX), None);
+        assert_eq!(
+            UNIX_EPOCH.checked_sub(Duration::from_secs(i64::MAX as u64 + 1)),
+            Some(min)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_before_epoch() {
+        let t = UNIX_EPOCH - Duration::from_millis(500);
+        let serialized = serde_json::to_string(&t).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"secs_since_epoch":-1,"nanos_since_epoch":500000000}"#
+        );
+        let deserialized: SystemTime = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(t, deserialized);
+
+        let deserialized: SystemTime = serde_json::from_str("[-2,250000000]").unwrap();
+        assert_eq!(deserialized, UNIX_EPOCH - Duration::from_millis(1750));
+
+        assert!(serde_json::from_str::<SystemTime>(
+            r#"{"secs_since_epoch":18446744073709551615,"nanos_since_epoch":0}"#
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip_is_lossless() {
+        let t = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        let serialized = serde_json::to_string(&t).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"secs_since_epoch":1700000000,"nanos_since_epoch":123456789}"#
+        );
+        let deserialized: SystemTime = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(t, deserialized);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_binary_round_trip() {
+        use bincode::Options;
+
+        for t in [
+            UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789),
+            UNIX_EPOCH - Duration::from_millis(500),
+            UNIX_EPOCH - Duration::new(1_700_000_000, 123_456_789),
+        ] {
+            let serialized = postcard::to_allocvec(&t).unwrap();
+            assert_eq!(postcard::from_bytes::<SystemTime>(&serialized).unwrap(), t);
+
+            let options = bincode::DefaultOptions::new();
+            let serialized = options.serialize(&t).unwrap();
+            assert_eq!(options.deserialize::<SystemTime>(&serialized).unwrap(), t);
+        }
+
+        // Later times are encoded the same way as `std::time::SystemTime`.
+        let t = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        let std_time = std::time::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        assert_eq!(
+            postcard::to_allocvec(&t).unwrap(),
+            postcard::to_allocvec(&std_time).unwrap()
+        );
+        let options = bincode::DefaultOptions::new();
+        assert_eq!(
+            options.serialize(&t).unwrap(),
+            options.serialize(&std_time).unwrap()
+        );
+    }
+
+    /// Property tests checking the arithmetic against a model that counts
+    /// nanoseconds since `UNIX_EPOCH` in an `i128`, which cannot overflow.
+    #[cfg(not(target_family = "wasm"))]
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        const NANOS: i128 = NANOS_PER_SEC as i128;
+
+        fn model(t: SystemTime) -> i128 {
+            i128::from(t.secs) * NANOS + i128::from(t.nanos)
+        }
+
+        fn from_model(nanos: i128) -> Option<SystemTime> {
+            let secs = i64::try_from(nanos.div_euclid(NANOS)).ok()?;
+            let nanos = nanos.rem_euclid(NANOS) as u32;
+            Some(SystemTime { secs, nanos })
+        }
+
+        fn hash(t: SystemTime) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        fn system_time() -> impl Strategy<Value = SystemTime> {
+            prop_oneof![
+                (any::<i64>(), 0..NANOS_PER_SEC),
+                // Times close together, so that equal values and carries are
+                // generated often.
+                (
+                    -3i64..3,
+                    prop_oneof![Just(0), Just(NANOS_PER_SEC - 1), 0..NANOS_PER_SEC]
+                ),
+            ]
+            .prop_map(|(secs, nanos)| SystemTime { secs, nanos })
+        }
+
+        fn duration() -> impl Strategy<Value = Duration> {
+            prop_oneof![
+                (any::<u64>(), 0..NANOS_PER_SEC),
+                (0u64..3, 0..NANOS_PER_SEC),
+            ]
+            .prop_map(|(secs, nanos)| Duration::new(secs, nanos))
+        }
+
+        proptest! {
+            #[test]
+            fn checked_add(t in system_time(), d in duration()) {
+                let expected = from_model(model(t) + d.as_nanos() as i128);
+                prop_assert_eq!(t.checked_add(d), expected);
+                if let Some(expected) = expected {
+                    prop_assert_eq!(t + d, expected);
+                    let mut u = t;
+                    u += d;
+                    prop_assert_eq!(u, expected);
+                }
+            }
+
+            #[test]
+            fn checked_sub(t in system_time(), d in duration()) {
+                let expected = from_model(model(t) - d.as_nanos() as i128);
+                prop_assert_eq!(t.checked_sub(d), expected);
+                if let Some(expected) = expected {
+                    prop_assert_eq!(t - d, expected);
+                    let mut u = t;
+                    u -= d;
+                    prop_assert_eq!(u, expected);
+                }
+            }
+
+            #[test]
+            fn add_sub_round_trip(t in system_time(), d in duration()) {
+                if let Some(u) = t.checked_add(d) {
+                    prop_assert_eq!(u - d, t);
+                    prop_assert_eq!(u.duration_since(t).unwrap(), d);
+                }
+            }
+
+            #[test]
+            fn duration_since(a in system_time(), b in system_time()) {
+                let diff = model(a) - model(b);
+                let expected = Duration::new(
+                    (diff.unsigned_abs() / NANOS as u128) as u64,
+                    (diff.unsigned_abs() % NANOS as u128) as u32,
+                );
+                match a.duration_since(b) {
+                    Ok(d) => {
+                        prop_assert!(diff >= 0);
+                        prop_assert_eq!(d, expected);
+                    }
+                    Err(e) => {
+                        prop_assert!(diff < 0);
+                        prop_assert_eq!(e.duration(), expected);
+                    }
+                }
+            }
+
+            #[test]
+            fn total_order_and_hash(a in system_time(), b in system_time()) {
+                prop_assert_eq!(a.cmp(&b), model(a).cmp(&model(b)));
+                prop_assert_eq!(a.partial_cmp(&b), Some(a.cmp(&b)));
+                prop_assert_eq!(a == b, model(a) == model(b));
+                if a == b {
+                    prop_assert_eq!(hash(a), hash(b));
+                }
+            }
+
+            #[test]
+            fn instant_arithmetic(a in any::<u64>(), d in duration()) {
+                let i = Instant(Duration::from_nanos(a));
+                let expected = Duration::from_nanos(a).checked_add(d).map(Instant);
+                prop_assert_eq!(i.checked_add(d), expected);
+                if let Some(j) = expected {
+                    prop_assert_eq!(j - d, i);
+                    prop_assert_eq!(j - i, d);
+                    prop_assert_eq!(i - j, Duration::ZERO);
+                    prop_assert!(i <= j);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,229 @@
+//! Breakdown of [`SystemTime`] into calendar fields.
+//!
+//! The UTC breakdown is plain arithmetic on the proleptic Gregorian calendar.
+//! The local breakdown asks JS for the UTC offset of the host's time zone at
+//! the given time, so daylight saving time is taken into account.
+
+#[cfg(not(target_os = "wasi"))]
+use js_sys::Date;
+
+#[cfg(not(target_os = "wasi"))]
+use super::JsTimeError;
+use super::SystemTime;
+
+pub(crate) const SECS_PER_DAY: i64 = 86_400;
+
+/// A [`SystemTime`] split into its calendar fields, as returned by
+/// [`SystemTime::to_utc_parts`] and, except on WASI,
+/// `SystemTime::to_local_parts`.
+///
+/// Dates use the proleptic Gregorian calendar, like JS `Date`. Years before 1
+/// are numbered astronomically, so the year before 1 is 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DateTimeParts {
+    pub year: i64,
+    /// In the range `1..=12`.
+    pub month: u32,
+    /// In the range `1..=31`.
+    pub day: u32,
+    /// In the range `0..=23`.
+    pub hour: u32,
+    /// In the range `0..=59`.
+    pub minute: u32,
+    /// In the range `0..=59`.
+    pub second: u32,
+    /// In the range `0..1_000_000_000`.
+    pub nanosecond: u32,
+    /// Days since Sunday, in the range `0..=6` like `Date.prototype.getDay`.
+    pub weekday: u32,
+    /// Offset from UTC in seconds, positive east of UTC. Always 0 for
+    /// [`SystemTime::to_utc_parts`].
+    pub offset: i32,
+}
+
+impl DateTimeParts {
+    /// Splits `secs` seconds since `UNIX_EPOCH` in the time zone `offset`
+    /// seconds east of UTC.
+    fn new(secs: i64, nanosecond: u32, offset: i32) -> DateTimeParts {
+        let secs = secs.saturating_add(i64::from(offset));
+        let days = secs.div_euclid(SECS_PER_DAY);
+        let secs_of_day = secs.rem_euclid(SECS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        DateTimeParts {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3600,
+            minute: secs_of_day / 60 % 60,
+            second: secs_of_day % 60,
+            nanosecond,
+            // 1970-01-01 was a Thursday.
+            weekday: (days + 4).rem_euclid(7) as u32,
+            offset,
+        }
+    }
+}
+
+impl SystemTime {
+    /// Splits this time into its calendar fields in UTC.
+    ///
+    /// This is plain arithmetic and does not call into JS.
+    pub fn to_utc_parts(&self) -> DateTimeParts {
+        DateTimeParts::new(self.secs, self.nanos, 0)
+    }
+
+    #[cfg(not(target_os = "wasi"))]
+    /// Returns the offset of the host's local time zone from UTC at this time,
+    /// in seconds, positive east of UTC.
+    ///
+    /// This is read from `Date.prototype.getTimezoneOffset` for this time, so
+    /// daylight saving time and historical changes of the time zone are taken
+    /// into account.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this time is out of the range of JS `Date`, which
+    /// is 100,000,000 days before or after `UNIX_EPOCH`.
+    pub fn local_offset(&self) -> Result<i32, JsTimeError> {
+        let minutes_west = Date::from(*self).get_timezone_offset();
+        if minutes_west.is_nan() {
+            return Err(JsTimeError::new("time value is out of range for Date"));
+        }
+        Ok((-minutes_west * 60.0).round() as i32)
+    }
+
+    #[cfg(not(target_os = "wasi"))]
+    /// Splits this time into its calendar fields in the host's local time
+    /// zone, using the offset returned by [`SystemTime::local_offset`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this time is out of the range of JS `Date`.
+    pub fn to_local_parts(&self) -> Result<DateTimeParts, JsTimeError> {
+        let offset = self.local_offset()?;
+        Ok(DateTimeParts::new(self.secs, self.nanos, offset))
+    }
+}
+
+/// Returns the number of days since 1970-01-01 of the given date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((i64::from(month) + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the date that is the given number of days after 1970-01-01.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+pub(crate) fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::UNIX_EPOCH;
+    use std::time::Duration;
+
+    #[test]
+    fn civil_days() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        for days in [-719_468, -1, 0, 1, 11_016, 11_017, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        assert_eq!(civil_from_days(2_932_896), (9999, 12, 31));
+        assert_eq!(civil_from_days(-719_468), (0, 3, 1));
+    }
+
+    #[test]
+    fn utc_parts() {
+        let t = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        assert_eq!(
+            t.to_utc_parts(),
+            DateTimeParts {
+                year: 2023,
+                month: 11,
+                day: 14,
+                hour: 22,
+                minute: 13,
+                second: 20,
+                nanosecond: 123_456_789,
+                weekday: 2,
+                offset: 0,
+            }
+        );
+
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951_782_400);
+        let parts = leap_day.to_utc_parts();
+        assert_eq!((parts.year, parts.month, parts.day), (2000, 2, 29));
+        assert_eq!(parts.weekday, 2);
+
+        let parts = (UNIX_EPOCH - Duration::from_nanos(1)).to_utc_parts();
+        assert_eq!(
+            (
+                parts.year,
+                parts.month,
+                parts.day,
+                parts.hour,
+                parts.minute,
+                parts.second
+            ),
+            (1969, 12, 31, 23, 59, 59)
+        );
+        assert_eq!(parts.nanosecond, 999_999_999);
+        assert_eq!(parts.weekday, 3);
+    }
+
+    #[test]
+    fn offset_parts() {
+        // 2023-11-14T22:13:20Z is 2023-11-15T03:43:20+05:30.
+        let parts = DateTimeParts::new(1_700_000_000, 0, 19_800);
+        assert_eq!(
+            (parts.year, parts.month, parts.day, parts.hour, parts.minute),
+            (2023, 11, 15, 3, 43)
+        );
+        assert_eq!(parts.weekday, 3);
+        assert_eq!(parts.offset, 19_800);
+
+        let parts = DateTimeParts::new(0, 0, -3600);
+        assert_eq!(
+            (parts.year, parts.month, parts.day, parts.hour),
+            (1969, 12, 31, 23)
+        );
+    }
+}
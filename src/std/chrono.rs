@@ -0,0 +1,86 @@
+//! Conversions between [`SystemTime`] and `chrono` date-times, enabled by the
+//! `chrono` feature.
+
+use chrono_crate::{DateTime, TimeZone, Utc};
+use std::time::Duration;
+
+use super::{OutOfRangeError, SystemTime};
+
+/// Returns the current time as a `DateTime<Utc>`.
+///
+/// Unlike `chrono::Utc::now`, this reads the installed
+/// [`ClockSource`](super::ClockSource).
+///
+/// # Panics
+///
+/// Panics if the clock source returns a time that `chrono` can not represent.
+pub fn now_utc() -> DateTime<Utc> {
+    DateTime::try_from(SystemTime::now()).expect("current time is out of range for chrono")
+}
+
+/// Leap seconds are folded into the following second.
+impl<Tz: TimeZone> From<DateTime<Tz>> for SystemTime {
+    fn from(date_time: DateTime<Tz>) -> SystemTime {
+        SystemTime {
+            secs: date_time.timestamp(),
+            nanos: 0,
+        } + Duration::from_nanos(u64::from(date_time.timestamp_subsec_nanos()))
+    }
+}
+
+impl TryFrom<SystemTime> for DateTime<Utc> {
+    type Error = OutOfRangeError;
+
+    fn try_from(time: SystemTime) -> Result<DateTime<Utc>, OutOfRangeError> {
+        DateTime::from_timestamp(time.secs, time.nanos).ok_or(OutOfRangeError(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::UNIX_EPOCH;
+    use chrono_crate::{FixedOffset, NaiveDate};
+
+    #[test]
+    fn round_trip() {
+        let date_time = DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap();
+        let time = SystemTime::from(date_time);
+        assert_eq!(time, UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789));
+        assert_eq!(DateTime::try_from(time), Ok(date_time));
+
+        let before_epoch = UNIX_EPOCH - Duration::new(14_182_940, 500);
+        let date_time = DateTime::<Utc>::try_from(before_epoch).unwrap();
+        assert_eq!(date_time.timestamp(), -14_182_941);
+        assert_eq!(SystemTime::from(date_time), before_epoch);
+    }
+
+    #[test]
+    fn offset_and_leap_second() {
+        let offset = FixedOffset::east_opt(5 * 3600).unwrap();
+        let date_time = offset.timestamp_opt(1_700_000_000, 0).unwrap();
+        assert_eq!(
+            SystemTime::from(date_time),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+        );
+
+        let leap = NaiveDate::from_ymd_opt(2016, 12, 31)
+            .unwrap()
+            .and_hms_nano_opt(23, 59, 59, 1_500_000_000)
+            .unwrap()
+            .and_utc();
+        assert_eq!(
+            SystemTime::from(leap),
+            UNIX_EPOCH + Duration::new(1_483_228_800, 500_000_000)
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let far_future = UNIX_EPOCH + Duration::from_secs(i64::MAX as u64);
+        assert_eq!(
+            DateTime::try_from(far_future),
+            Err::<DateTime<Utc>, _>(OutOfRangeError(()))
+        );
+    }
+}
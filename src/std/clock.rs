@@ -0,0 +1,177 @@
+//! The source of the current time used by this crate.
+//!
+//! By default the time is read from `performance.now()`,
+//! `performance.timeOrigin` and `Date.now()` on the JS global object, or from
+//! the WASI clocks on WASI targets. Hosts that do not provide these, or that
+//! need a different notion of time, can install their own [`ClockSource`]
+//! once at startup with [`set_clock_source`]. The installed source is used by
+//! [`Instant::now`], [`SystemTime::now`] and the timers.
+
+use std::sync::atomic::AtomicPtr;
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::Duration;
+
+#[cfg(not(target_os = "wasi"))]
+use crate::js::{performance_now, performance_time_origin};
+
+#[cfg(not(target_os = "wasi"))]
+use super::Instant;
+use super::{SystemTime, UNIX_EPOCH};
+
+/// A source of monotonic and wall-clock time.
+///
+/// The monotonic clock is measured from a fixed point, the time origin, that
+/// must not change once the source is installed. [`super::Instant::now`] never goes
+/// backwards, even if the source does.
+pub trait ClockSource: Send + Sync {
+    /// Returns the current monotonic time, as elapsed since the time origin.
+    fn monotonic_now(&self) -> Duration;
+
+    /// Returns the wall-clock time at which the monotonic clock was zero.
+    fn time_origin(&self) -> SystemTime;
+
+    /// Returns the current wall-clock time.
+    fn system_now(&self) -> SystemTime;
+}
+
+/// The default clock source, backed by the `performance` and `Date` objects
+/// of the JS global object.
+#[cfg(not(target_os = "wasi"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsClockSource;
+
+#[cfg(not(target_os = "wasi"))]
+impl ClockSource for JsClockSource {
+    fn monotonic_now(&self) -> Duration {
+        Instant::from_high_res_timestamp(performance_now()).0
+    }
+
+    fn time_origin(&self) -> SystemTime {
+        SystemTime::try_from_js_millis(performance_time_origin())
+            .expect("`performance.timeOrigin` is not a valid time")
+    }
+
+    fn system_now(&self) -> SystemTime {
+        SystemTime::try_from_js_millis(js_sys::Date::now())
+            .expect("`Date.now()` returned an invalid time")
+    }
+}
+
+/// A clock source backed by `std::time`.
+///
+/// This is the default on WASI targets, where the standard library reads
+/// `clock_time_get` on preview 1 and `wasi:clocks` on preview 2. The time
+/// origin is the moment this source was created.
+#[derive(Clone, Copy, Debug)]
+pub struct StdClockSource {
+    origin: std::time::Instant,
+    time_origin: SystemTime,
+}
+
+impl StdClockSource {
+    pub fn new() -> StdClockSource {
+        StdClockSource {
+            origin: std::time::Instant::now(),
+            time_origin: from_std(std::time::SystemTime::now()),
+        }
+    }
+}
+
+impl Default for StdClockSource {
+    fn default() -> StdClockSource {
+        StdClockSource::new()
+    }
+}
+
+impl ClockSource for StdClockSource {
+    fn monotonic_now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    fn time_origin(&self) -> SystemTime {
+        self.time_origin
+    }
+
+    fn system_now(&self) -> SystemTime {
+        from_std(std::time::SystemTime::now())
+    }
+}
+
+fn from_std(time: std::time::SystemTime) -> SystemTime {
+    match time.duration_since(std::time::UNIX_EPOCH) {
+        Ok(since) => UNIX_EPOCH + since,
+        Err(err) => UNIX_EPOCH - err.duration(),
+    }
+}
+
+static CLOCK_SOURCE: AtomicPtr<Box<dyn ClockSource>> = AtomicPtr::new(EMPTY_SOURCE);
+const EMPTY_SOURCE: *mut Box<dyn ClockSource> = std::ptr::null_mut();
+
+/// Error returned from [`set_clock_source`].
+#[derive(Clone, Debug)]
+pub struct SetClockSourceError(());
+
+/// Installs `source` as the clock source for the rest of the program.
+///
+/// # Errors
+///
+/// Fails if a clock source was already installed, or if the time was already
+/// read through the default clock source. Once a clock source is in use
+/// it can not be replaced, so this should be called at startup.
+pub fn set_clock_source<C: ClockSource + 'static>(source: C) -> Result<(), SetClockSourceError> {
+    install(Box::new(source))
+}
+
+fn install(source: Box<dyn ClockSource>) -> Result<(), SetClockSourceError> {
+    let val = Box::into_raw(Box::new(source));
+    match CLOCK_SOURCE.compare_exchange(EMPTY_SOURCE, val, SeqCst, SeqCst) {
+        Ok(_) => Ok(()),
+        Err(_) => {
+            drop(unsafe { Box::from_raw(val) });
+            Err(SetClockSourceError(()))
+        }
+    }
+}
+
+/// Returns the installed clock source, installing the default one if none was
+/// installed yet.
+pub(crate) fn clock_source() -> &'static dyn ClockSource {
+    let mut source = CLOCK_SOURCE.load(SeqCst);
+    if source == EMPTY_SOURCE {
+        // Losing the race is fine, someone else installed a source.
+        let _ = install(default_source());
+        source = CLOCK_SOURCE.load(SeqCst);
+    }
+
+    // The installed source is never freed.
+    unsafe { &**source }
+}
+
+#[cfg(not(target_os = "wasi"))]
+fn default_source() -> Box<dyn ClockSource> {
+    Box::new(JsClockSource)
+}
+
+#[cfg(target_os = "wasi")]
+fn default_source() -> Box<dyn ClockSource> {
+    Box::new(StdClockSource::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn std_clock_source() {
+        let source = StdClockSource::new();
+        let before = source.system_now();
+        let elapsed = source.monotonic_now();
+        assert!(source.time_origin() <= before);
+        assert!(source.monotonic_now() >= elapsed);
+        assert!(source.system_now() >= before);
+        assert_eq!(
+            from_std(std::time::UNIX_EPOCH - Duration::from_millis(1500)),
+            UNIX_EPOCH - Duration::from_millis(1500)
+        );
+    }
+}
@@ -0,0 +1,504 @@
+//! Formatting and parsing of [`SystemTime`] as RFC 3339 timestamps and HTTP
+//! dates.
+//!
+//! Everything here is plain arithmetic on the proleptic Gregorian calendar in
+//! UTC, without calling into JS, so the results do not depend on the host.
+
+use std::fmt;
+
+use super::calendar::{days_from_civil, days_in_month, SECS_PER_DAY};
+use super::{OutOfRangeError, SystemTime, NANOS_PER_SEC};
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Error returned when a string is not a valid timestamp in the expected
+/// format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError(&'static str);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Validates the fields and converts them to a `SystemTime`, `offset` seconds
+/// east of UTC. `time` is the hour, minute and second.
+///
+/// A leap second (`second == 60`) is only valid at 23:59 UTC, and is folded
+/// into the following second.
+fn from_fields(
+    year: i64,
+    month: u32,
+    day: u32,
+    (hour, minute, second): (u32, u32, u32),
+    nanos: u32,
+    offset: i64,
+) -> Result<SystemTime, ParseError> {
+    if !(1..=12).contains(&month) {
+        return Err(ParseError("month is out of range"));
+    }
+    if day == 0 || day > days_in_month(year, month) {
+        return Err(ParseError("day is out of range"));
+    }
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(ParseError("time is out of range"));
+    }
+    let time_of_day = i64::from(hour * 3600 + minute * 60 + second) - offset;
+    if second == 60 && time_of_day.rem_euclid(SECS_PER_DAY) != 0 {
+        return Err(ParseError("leap second is not at the end of a UTC day"));
+    }
+    let secs = days_from_civil(year, month, day) * SECS_PER_DAY + time_of_day;
+    Ok(SystemTime { secs, nanos })
+}
+
+/// A cursor over the bytes of a timestamp being parsed.
+struct Parser<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Parser<'a> {
+        Parser {
+            input: input.as_bytes(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.first().copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let (&first, rest) = self.input.split_first()?;
+        self.input = rest;
+        Some(first)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), ParseError> {
+        match self.next() {
+            Some(byte) if byte == expected => Ok(()),
+            _ => Err(ParseError("unexpected character")),
+        }
+    }
+
+    /// Consumes `expected` if the input starts with it.
+    fn eat(&mut self, expected: &str) -> bool {
+        match self.input.strip_prefix(expected.as_bytes()) {
+            Some(rest) => {
+                self.input = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Parses exactly `count` decimal digits.
+    fn digits(&mut self, count: usize) -> Result<u32, ParseError> {
+        let mut value = 0;
+        for _ in 0..count {
+            match self.next() {
+                Some(byte @ b'0'..=b'9') => value = value * 10 + u32::from(byte - b'0'),
+                _ => return Err(ParseError("expected a digit")),
+            }
+        }
+        Ok(value)
+    }
+
+    /// Parses one of `names`, returning its index.
+    fn name(&mut self, names: &[&str]) -> Result<usize, ParseError> {
+        names
+            .iter()
+            .position(|name| self.eat(name))
+            .ok_or(ParseError("unknown day or month name"))
+    }
+
+    fn end(&self) -> Result<(), ParseError> {
+        if self.input.is_empty() {
+            Ok(())
+        } else {
+            Err(ParseError("unexpected trailing characters"))
+        }
+    }
+}
+
+impl SystemTime {
+    /// Formats this time as an RFC 3339 timestamp in UTC, such as
+    /// `2023-11-14T22:13:20.123Z`.
+    ///
+    /// The fraction of a second is omitted when it is zero, and otherwise
+    /// printed with 3, 6 or 9 digits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the year is outside of `0000` to `9999`, which can
+    /// not be represented in RFC 3339.
+    pub fn to_rfc3339(&self) -> Result<String, OutOfRangeError> {
+        if !(0..=9999).contains(&self.to_utc_parts().year) {
+            return Err(OutOfRangeError(()));
+        }
+        Ok(self.to_string())
+    }
+
+    /// Parses an RFC 3339 timestamp, such as `2023-11-14T22:13:20.123Z` or
+    /// `2023-11-15T03:13:20+05:00`.
+    ///
+    /// Lowercase `t` and `z`, and a space instead of `T` are accepted too.
+    /// Fractions of a second beyond nanoseconds are truncated, and a leap
+    /// second, which is only valid at `23:59:60` UTC, is folded into the
+    /// following second.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `s` is not a valid RFC 3339 timestamp.
+    pub fn parse_rfc3339(s: &str) -> Result<SystemTime, ParseError> {
+        let mut parser = Parser::new(s);
+        let year = parser.digits(4)?;
+        parser.expect(b'-')?;
+        let month = parser.digits(2)?;
+        parser.expect(b'-')?;
+        let day = parser.digits(2)?;
+        match parser.next() {
+            Some(b'T' | b't' | b' ') => {}
+            _ => return Err(ParseError("expected `T` between date and time")),
+        }
+        let hour = parser.digits(2)?;
+        parser.expect(b':')?;
+        let minute = parser.digits(2)?;
+        parser.expect(b':')?;
+        let second = parser.digits(2)?;
+
+        let mut nanos = 0;
+        if parser.eat(".") {
+            let mut scale = NANOS_PER_SEC;
+            while let Some(byte @ b'0'..=b'9') = parser.peek() {
+                parser.next();
+                scale /= 10;
+                nanos += u32::from(byte - b'0') * scale;
+            }
+            if scale == NANOS_PER_SEC {
+                return Err(ParseError("expected a digit"));
+            }
+        }
+
+        let offset = match parser.next() {
+            Some(b'Z' | b'z') => 0,
+            Some(sign @ (b'+' | b'-')) => {
+                let hours = parser.digits(2)?;
+                parser.expect(b':')?;
+                let minutes = parser.digits(2)?;
+                if hours > 23 || minutes > 59 {
+                    return Err(ParseError("offset is out of range"));
+                }
+                let offset = i64::from(hours * 3600 + minutes * 60);
+                if sign == b'-' {
+                    -offset
+                } else {
+                    offset
+                }
+            }
+            _ => return Err(ParseError("expected `Z` or an offset")),
+        };
+        parser.end()?;
+
+        from_fields(
+            i64::from(year),
+            month,
+            day,
+            (hour, minute, second),
+            nanos,
+            offset,
+        )
+    }
+
+    /// Formats this time as an HTTP date (the IMF-fixdate format of RFC 7231),
+    /// such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+    ///
+    /// HTTP dates have no fraction of a second, so it is truncated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the year is outside of `0000` to `9999`, which can
+    /// not be represented in an HTTP date.
+    pub fn to_http_date(&self) -> Result<String, OutOfRangeError> {
+        let date_time = self.to_utc_parts();
+        if !(0..=9999).contains(&date_time.year) {
+            return Err(OutOfRangeError(()));
+        }
+        Ok(format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            &WEEKDAYS[date_time.weekday as usize][..3],
+            date_time.day,
+            MONTHS[date_time.month as usize - 1],
+            date_time.year,
+            date_time.hour,
+            date_time.minute,
+            date_time.second,
+        ))
+    }
+
+    /// Parses an HTTP date, as found in headers such as `Date` or
+    /// `Last-Modified`.
+    ///
+    /// Besides the IMF-fixdate format (`Sun, 06 Nov 1994 08:49:37 GMT`), the
+    /// obsolete RFC 850 (`Sunday, 06-Nov-94 08:49:37 GMT`) and asctime
+    /// (`Sun Nov  6 08:49:37 1994`) formats are accepted, as RFC 7231 requires.
+    /// Two-digit RFC 850 years from `70` to `99` are in the 20th century, the
+    /// others in the 21st.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `s` is not a valid HTTP date, including when the
+    /// day of the week does not match the date.
+    pub fn parse_http_date(s: &str) -> Result<SystemTime, ParseError> {
+        let mut parser = Parser::new(s);
+        let weekday = parser.name(&WEEKDAYS.map(|name| &name[..3]))?;
+
+        let (year, month, day, time) = if parser.eat(", ") {
+            // IMF-fixdate
+            let day = parser.digits(2)?;
+            parser.expect(b' ')?;
+            let month = parser.name(&MONTHS)?;
+            parser.expect(b' ')?;
+            let year = i64::from(parser.digits(4)?);
+            parser.expect(b' ')?;
+            let time = parse_http_time(&mut parser)?;
+            if !parser.eat(" GMT") {
+                return Err(ParseError("expected `GMT`"));
+            }
+            (year, month, day, time)
+        } else if parser.eat(" ") {
+            // asctime
+            let month = parser.name(&MONTHS)?;
+            parser.expect(b' ')?;
+            let day = if parser.eat(" ") {
+                parser.digits(1)?
+            } else {
+                parser.digits(2)?
+            };
+            parser.expect(b' ')?;
+            let time = parse_http_time(&mut parser)?;
+            parser.expect(b' ')?;
+            let year = i64::from(parser.digits(4)?);
+            (year, month, day, time)
+        } else {
+            // RFC 850
+            if !parser.eat(&WEEKDAYS[weekday][3..]) || !parser.eat(", ") {
+                return Err(ParseError("unknown day or month name"));
+            }
+            let day = parser.digits(2)?;
+            parser.expect(b'-')?;
+            let month = parser.name(&MONTHS)?;
+            parser.expect(b'-')?;
+            let year = i64::from(parser.digits(2)?);
+            let year = if year >= 70 { 1900 + year } else { 2000 + year };
+            parser.expect(b' ')?;
+            let time = parse_http_time(&mut parser)?;
+            if !parser.eat(" GMT") {
+                return Err(ParseError("expected `GMT`"));
+            }
+            (year, month, day, time)
+        };
+        parser.end()?;
+
+        let time = from_fields(year, month as u32 + 1, day, time, 0, 0)?;
+        // Checked against the date as written, as a leap second is folded into
+        // the following day.
+        let days = days_from_civil(year, month as u32 + 1, day);
+        if (days + 4).rem_euclid(7) as usize != weekday {
+            return Err(ParseError("day of the week does not match the date"));
+        }
+        Ok(time)
+    }
+}
+
+fn parse_http_time(parser: &mut Parser<'_>) -> Result<(u32, u32, u32), ParseError> {
+    let hour = parser.digits(2)?;
+    parser.expect(b':')?;
+    let minute = parser.digits(2)?;
+    parser.expect(b':')?;
+    let second = parser.digits(2)?;
+    Ok((hour, minute, second))
+}
+
+/// Formats the time like [`SystemTime::to_rfc3339`]. Years outside of `0000`
+/// to `9999` are printed with a sign and at least six digits instead, like JS
+/// `Date.prototype.toISOString` does.
+impl fmt::Display for SystemTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let date_time = self.to_utc_parts();
+        if (0..=9999).contains(&date_time.year) {
+            write!(f, "{:04}", date_time.year)?;
+        } else {
+            write!(f, "{:+07}", date_time.year)?;
+        }
+        write!(
+            f,
+            "-{:02}-{:02}T{:02}:{:02}:{:02}",
+            date_time.month, date_time.day, date_time.hour, date_time.minute, date_time.second
+        )?;
+        match date_time.nanosecond {
+            0 => {}
+            nanos if nanos.is_multiple_of(1_000_000) => write!(f, ".{:03}", nanos / 1_000_000)?,
+            nanos if nanos.is_multiple_of(1_000) => write!(f, ".{:06}", nanos / 1_000)?,
+            nanos => write!(f, ".{:09}", nanos)?,
+        }
+        f.write_str("Z")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::UNIX_EPOCH;
+    use std::time::Duration;
+
+    #[test]
+    fn format_rfc3339() {
+        assert_eq!(
+            UNIX_EPOCH.to_rfc3339().as_deref(),
+            Ok("1970-01-01T00:00:00Z")
+        );
+        let time = UNIX_EPOCH + Duration::new(1_700_000_000, 123_000_000);
+        assert_eq!(time.to_rfc3339().as_deref(), Ok("2023-11-14T22:13:20.123Z"));
+        let time = UNIX_EPOCH + Duration::new(951_782_400, 1_000);
+        assert_eq!(time.to_string(), "2000-02-29T00:00:00.000001Z");
+        let time = UNIX_EPOCH - Duration::new(0, 1);
+        assert_eq!(time.to_string(), "1969-12-31T23:59:59.999999999Z");
+        let time = UNIX_EPOCH + Duration::from_secs(253_402_300_800);
+        assert_eq!(time.to_string(), "+010000-01-01T00:00:00Z");
+        assert!(time.to_rfc3339().is_err());
+        let time = UNIX_EPOCH - Duration::from_secs(62_198_755_200);
+        assert_eq!(time.to_string(), "-000001-01-01T00:00:00Z");
+        assert!(time.to_rfc3339().is_err());
+    }
+
+    #[test]
+    fn parse_rfc3339() {
+        let time = UNIX_EPOCH + Duration::new(1_700_000_000, 123_000_000);
+        assert_eq!(
+            SystemTime::parse_rfc3339("2023-11-14T22:13:20.123Z"),
+            Ok(time)
+        );
+        assert_eq!(
+            SystemTime::parse_rfc3339("2023-11-15T03:13:20.123+05:00"),
+            Ok(time)
+        );
+        assert_eq!(
+            SystemTime::parse_rfc3339("2023-11-14 17:13:20.1230000009-05:00"),
+            Ok(time)
+        );
+        assert_eq!(
+            SystemTime::parse_rfc3339("2016-12-31t23:59:60z"),
+            SystemTime::parse_rfc3339("2017-01-01T00:00:00Z")
+        );
+        assert_eq!(
+            SystemTime::parse_rfc3339("2017-01-01T05:29:60+05:30"),
+            SystemTime::parse_rfc3339("2017-01-01T00:00:00Z")
+        );
+        for invalid in [
+            "",
+            "2023-11-14",
+            "2023-11-14T22:13:20",
+            "2023-11-14T22:13:20.Z",
+            "2023-13-14T22:13:20Z",
+            "2023-02-29T22:13:20Z",
+            "2023-11-14T24:13:20Z",
+            "2023-11-14T22:13:60Z",
+            "2016-12-31T23:58:60Z",
+            "2016-12-31T23:59:60+01:00",
+            "2023-11-14T22:13:20+24:00",
+            "2023-11-14T22:13:20Z ",
+            "+02023-11-14T22:13:20Z",
+        ] {
+            assert!(SystemTime::parse_rfc3339(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn rfc3339_round_trip() {
+        for secs in [
+            -62_167_219_200,
+            -62_135_596_800,
+            -1,
+            0,
+            951_782_400,
+            1_700_000_000,
+            253_402_300_799,
+        ] {
+            for nanos in [0, 1, 1_000, 500_000_000] {
+                let time = SystemTime { secs, nanos };
+                let formatted = time.to_rfc3339().unwrap();
+                assert_eq!(SystemTime::parse_rfc3339(&formatted), Ok(time));
+            }
+        }
+    }
+
+    #[test]
+    fn http_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        assert_eq!(
+            time.to_http_date().as_deref(),
+            Ok("Sun, 06 Nov 1994 08:49:37 GMT")
+        );
+        assert_eq!(
+            (time + Duration::from_millis(999))
+                .to_http_date()
+                .as_deref(),
+            Ok("Sun, 06 Nov 1994 08:49:37 GMT")
+        );
+        assert!((UNIX_EPOCH + Duration::from_secs(253_402_300_800))
+            .to_http_date()
+            .is_err());
+        assert!((UNIX_EPOCH - Duration::from_secs(62_198_755_200))
+            .to_http_date()
+            .is_err());
+        assert_eq!(
+            SystemTime::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Ok(time)
+        );
+        assert_eq!(
+            SystemTime::parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+            Ok(time)
+        );
+        assert_eq!(
+            SystemTime::parse_http_date("Sun Nov  6 08:49:37 1994"),
+            Ok(time)
+        );
+        assert_eq!(
+            SystemTime::parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"),
+            Ok(UNIX_EPOCH)
+        );
+        assert_eq!(
+            SystemTime::parse_http_date("Sat, 31 Dec 2016 23:59:60 GMT"),
+            SystemTime::parse_http_date("Sun, 01 Jan 2017 00:00:00 GMT")
+        );
+        for invalid in [
+            "",
+            "Mon, 06 Nov 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 08:49:37 UTC",
+            "Sun, 6 Nov 1994 08:49:37 GMT",
+            "Sun, 31 Nov 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 08:49:37 GMT ",
+            "Sun, 06 Nov 1994 08:49 GMT",
+            "Sunday, 06 Nov 1994 08:49:37 GMT",
+            "Sun Nov 6 08:49:37 1994",
+            "Sun, 31 Dec 2016 23:59:60 GMT",
+        ] {
+            assert!(SystemTime::parse_http_date(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
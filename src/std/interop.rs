@@ -0,0 +1,255 @@
+//! Conversions between the time types and JS values.
+//!
+//! [`SystemTime`] maps to milliseconds since `UNIX_EPOCH`, as used by JS
+//! `Date` objects, and [`Instant`] maps to milliseconds since
+//! `performance.timeOrigin`, as returned by `performance.now()`. Both types
+//! can be used directly in `#[wasm_bindgen]` function signatures, where they
+//! appear as a `number` on the JS side.
+//!
+//! The conversions from and to JS types are not available on WASI targets.
+
+use std::fmt;
+use std::time::Duration;
+
+#[cfg(not(target_os = "wasi"))]
+use js_sys::Date;
+#[cfg(not(target_os = "wasi"))]
+use wasm_bindgen::convert::{FromWasmAbi, IntoWasmAbi};
+#[cfg(not(target_os = "wasi"))]
+use wasm_bindgen::describe::WasmDescribe;
+#[cfg(not(target_os = "wasi"))]
+use wasm_bindgen::{JsCast, JsValue};
+
+#[cfg(not(target_os = "wasi"))]
+use super::{Instant, SystemTime};
+use super::{MILLIS_PER_SEC, NANOS_PER_MILLI};
+
+/// Error returned when a JS value can not be converted to a time type.
+///
+/// This happens for invalid dates such as `new Date(NaN)`, for values that are
+/// not numbers, and for numbers that are out of range for the target type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsTimeError(&'static str);
+
+impl JsTimeError {
+    pub(crate) fn new(reason: &'static str) -> JsTimeError {
+        JsTimeError(reason)
+    }
+}
+
+impl fmt::Display for JsTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for JsTimeError {}
+
+/// Converts a number of milliseconds, as taken by `setTimeout` and most other
+/// JS APIs, to a `Duration`.
+///
+/// # Errors
+///
+/// Returns an error if `millis` is `NaN`, infinite, negative or too large to
+/// fit in a `Duration`.
+pub fn duration_from_js_millis(millis: f64) -> Result<Duration, JsTimeError> {
+    if !millis.is_finite() {
+        return Err(JsTimeError::new("duration is not a finite number"));
+    }
+    if millis < 0.0 {
+        return Err(JsTimeError::new("duration is negative"));
+    }
+    Duration::try_from_secs_f64(millis / MILLIS_PER_SEC)
+        .map_err(|_| JsTimeError::new("duration is out of range"))
+}
+
+/// Converts a `Duration` to a number of milliseconds, as taken by `setTimeout`
+/// and most other JS APIs.
+pub fn duration_to_js_millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / NANOS_PER_MILLI
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl TryFrom<&Date> for SystemTime {
+    type Error = JsTimeError;
+
+    fn try_from(date: &Date) -> Result<SystemTime, JsTimeError> {
+        SystemTime::try_from_js_millis(date.get_time())
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl TryFrom<Date> for SystemTime {
+    type Error = JsTimeError;
+
+    fn try_from(date: Date) -> Result<SystemTime, JsTimeError> {
+        SystemTime::try_from(&date)
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+/// The resulting `Date` only has millisecond precision, any sub-millisecond
+/// part is truncated.
+impl From<SystemTime> for Date {
+    fn from(time: SystemTime) -> Date {
+        Date::new(&JsValue::from_f64(time.to_js_millis()))
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+/// Accepts either a `Date` or a number of milliseconds since `UNIX_EPOCH`.
+impl TryFrom<&JsValue> for SystemTime {
+    type Error = JsTimeError;
+
+    fn try_from(value: &JsValue) -> Result<SystemTime, JsTimeError> {
+        if let Some(date) = value.dyn_ref::<Date>() {
+            SystemTime::try_from(date)
+        } else if let Some(millis) = value.as_f64() {
+            SystemTime::try_from_js_millis(millis)
+        } else {
+            Err(JsTimeError::new("expected a Date or a number"))
+        }
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl TryFrom<JsValue> for SystemTime {
+    type Error = JsTimeError;
+
+    fn try_from(value: JsValue) -> Result<SystemTime, JsTimeError> {
+        SystemTime::try_from(&value)
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+/// Converts to a number of milliseconds since `UNIX_EPOCH`.
+impl From<SystemTime> for JsValue {
+    fn from(time: SystemTime) -> JsValue {
+        JsValue::from_f64(time.to_js_millis())
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+/// Accepts a number of milliseconds since `performance.timeOrigin`, such as a
+/// value returned by `performance.now()`.
+impl TryFrom<&JsValue> for Instant {
+    type Error = JsTimeError;
+
+    fn try_from(value: &JsValue) -> Result<Instant, JsTimeError> {
+        match value.as_f64() {
+            Some(millis) => Instant::try_from_high_res_timestamp(millis),
+            None => Err(JsTimeError::new("expected a number")),
+        }
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl TryFrom<JsValue> for Instant {
+    type Error = JsTimeError;
+
+    fn try_from(value: JsValue) -> Result<Instant, JsTimeError> {
+        Instant::try_from(&value)
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+/// Converts to a number of milliseconds since `performance.timeOrigin`.
+impl From<Instant> for JsValue {
+    fn from(instant: Instant) -> JsValue {
+        JsValue::from_f64(instant.as_high_res_timestamp())
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl WasmDescribe for SystemTime {
+    fn describe() {
+        f64::describe()
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl IntoWasmAbi for SystemTime {
+    type Abi = <f64 as IntoWasmAbi>::Abi;
+
+    fn into_abi(self) -> Self::Abi {
+        self.to_js_millis().into_abi()
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+/// Throws a JS exception when the number passed in is not a valid time.
+impl FromWasmAbi for SystemTime {
+    type Abi = <f64 as FromWasmAbi>::Abi;
+
+    unsafe fn from_abi(js: Self::Abi) -> Self {
+        match SystemTime::try_from_js_millis(f64::from_abi(js)) {
+            Ok(time) => time,
+            Err(err) => wasm_bindgen::throw_str(&err.to_string()),
+        }
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl WasmDescribe for Instant {
+    fn describe() {
+        f64::describe()
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl IntoWasmAbi for Instant {
+    type Abi = <f64 as IntoWasmAbi>::Abi;
+
+    fn into_abi(self) -> Self::Abi {
+        self.as_high_res_timestamp().into_abi()
+    }
+}
+
+#[cfg(not(target_os = "wasi"))]
+/// Throws a JS exception when the number passed in is not a valid instant.
+impl FromWasmAbi for Instant {
+    type Abi = <f64 as FromWasmAbi>::Abi;
+
+    unsafe fn from_abi(js: Self::Abi) -> Self {
+        match Instant::try_from_high_res_timestamp(f64::from_abi(js)) {
+            Ok(instant) => instant,
+            Err(err) => wasm_bindgen::throw_str(&err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_millis() {
+        assert_eq!(
+            duration_from_js_millis(1500.25),
+            Ok(Duration::from_micros(1_500_250))
+        );
+        assert_eq!(
+            duration_to_js_millis(Duration::from_micros(1_500_250)),
+            1500.25
+        );
+        assert!(duration_from_js_millis(f64::NAN).is_err());
+        assert!(duration_from_js_millis(f64::INFINITY).is_err());
+        assert!(duration_from_js_millis(-1.0).is_err());
+        assert!(duration_from_js_millis(1e300).is_err());
+    }
+
+    #[cfg(not(target_os = "wasi"))]
+    #[test]
+    fn invalid_js_millis() {
+        assert!(SystemTime::try_from_js_millis(f64::NAN).is_err());
+        assert!(SystemTime::try_from_js_millis(f64::NEG_INFINITY).is_err());
+        assert!(SystemTime::try_from_js_millis(1e300).is_err());
+        assert!(SystemTime::try_from_js_millis(-8.64e15).is_ok());
+        assert!(Instant::try_from_high_res_timestamp(f64::NAN).is_err());
+        assert!(Instant::try_from_high_res_timestamp(-1.0).is_err());
+        assert_eq!(
+            Instant::try_from_high_res_timestamp(12.5).map(|i| i.as_high_res_timestamp()),
+            Ok(12.5)
+        );
+    }
+}
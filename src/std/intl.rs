@@ -0,0 +1,319 @@
+//! Locale-aware formatting of times through `Intl.DateTimeFormat` and
+//! `Intl.RelativeTimeFormat`, enabled by the `intl` feature.
+//!
+//! Creating an `Intl` formatter is slow, so the formatters are cached per
+//! thread for each combination of locale and options that is used.
+//!
+//! This is not available on WASI targets.
+
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use js_sys::{Object, Reflect};
+use wasm_bindgen::JsValue;
+
+use super::SystemTime;
+use crate::js::{DateTimeFormat, RelativeTimeFormat};
+
+/// Error returned when the locale or the options are rejected by `Intl`, or
+/// when the time is out of the range of JS `Date`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntlError(String);
+
+impl IntlError {
+    fn from_js(error: JsValue) -> IntlError {
+        let message = Reflect::get(&error, &JsValue::from_str("message"))
+            .ok()
+            .and_then(|message| message.as_string())
+            .unwrap_or_else(|| "invalid locale or options".to_string());
+        IntlError(message)
+    }
+}
+
+impl fmt::Display for IntlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for IntlError {}
+
+/// The length of a date or time formatted by [`SystemTime::format_locale`],
+/// the `dateStyle` and `timeStyle` options of `Intl.DateTimeFormat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DateTimeStyle {
+    /// Like `Tuesday, November 14, 2023` in `en-US`.
+    Full,
+    /// Like `November 14, 2023` in `en-US`.
+    Long,
+    /// Like `Nov 14, 2023` in `en-US`.
+    Medium,
+    /// Like `11/14/23` in `en-US`.
+    Short,
+}
+
+impl DateTimeStyle {
+    fn as_str(self) -> &'static str {
+        match self {
+            DateTimeStyle::Full => "full",
+            DateTimeStyle::Long => "long",
+            DateTimeStyle::Medium => "medium",
+            DateTimeStyle::Short => "short",
+        }
+    }
+}
+
+/// Options of [`SystemTime::format_locale`], a subset of the options of
+/// `Intl.DateTimeFormat`.
+///
+/// Fields left as `None` use the default of the locale. Without a date or time
+/// style, only the date is printed in numeric form.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DateTimeFormatOptions {
+    /// How to print the date, or `None` to leave it out when `time_style` is
+    /// set.
+    pub date_style: Option<DateTimeStyle>,
+    /// How to print the time of day, or `None` to leave it out.
+    pub time_style: Option<DateTimeStyle>,
+    /// An IANA time zone name such as `Europe/Berlin` or `UTC`, or `None` for
+    /// the host's time zone.
+    pub time_zone: Option<String>,
+    /// Whether to use a 12-hour clock, or `None` for the default of the
+    /// locale.
+    pub hour12: Option<bool>,
+}
+
+impl DateTimeFormatOptions {
+    fn to_js(&self) -> Object {
+        let options = Object::new();
+        if let Some(style) = self.date_style {
+            set(&options, "dateStyle", &JsValue::from_str(style.as_str()));
+        }
+        if let Some(style) = self.time_style {
+            set(&options, "timeStyle", &JsValue::from_str(style.as_str()));
+        }
+        if let Some(time_zone) = &self.time_zone {
+            set(&options, "timeZone", &JsValue::from_str(time_zone));
+        }
+        if let Some(hour12) = self.hour12 {
+            set(&options, "hour12", &JsValue::from_bool(hour12));
+        }
+        options
+    }
+}
+
+/// Whether [`format_relative`] may use phrases such as `yesterday` instead of
+/// `1 day ago`, the `numeric` option of `Intl.RelativeTimeFormat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RelativeNumeric {
+    /// Always print a number, like `1 day ago`.
+    #[default]
+    Always,
+    /// Use a phrase where the locale has one, like `yesterday`.
+    Auto,
+}
+
+/// The length of the units printed by [`format_relative`], the `style` option
+/// of `Intl.RelativeTimeFormat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RelativeStyle {
+    /// Like `in 3 months` in `en`.
+    #[default]
+    Long,
+    /// Like `in 3 mo.` in `en`.
+    Short,
+    /// Like `in 3mo` in `en`, or the same as `Short` in many locales.
+    Narrow,
+}
+
+/// Options of [`format_relative`], the options of `Intl.RelativeTimeFormat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RelativeTimeFormatOptions {
+    pub numeric: RelativeNumeric,
+    pub style: RelativeStyle,
+}
+
+impl RelativeTimeFormatOptions {
+    fn to_js(self) -> Object {
+        let options = Object::new();
+        let numeric = match self.numeric {
+            RelativeNumeric::Always => "always",
+            RelativeNumeric::Auto => "auto",
+        };
+        set(&options, "numeric", &JsValue::from_str(numeric));
+        let style = match self.style {
+            RelativeStyle::Long => "long",
+            RelativeStyle::Short => "short",
+            RelativeStyle::Narrow => "narrow",
+        };
+        set(&options, "style", &JsValue::from_str(style));
+        options
+    }
+}
+
+fn set(options: &Object, key: &str, value: &JsValue) {
+    Reflect::set(options, &JsValue::from_str(key), value)
+        .expect("setting a property of a plain object failed");
+}
+
+fn locales(locale: Option<&str>) -> JsValue {
+    locale.map_or(JsValue::UNDEFINED, JsValue::from_str)
+}
+
+thread_local! {
+    static DATE_TIME_FORMATS: RefCell<HashMap<(Option<String>, DateTimeFormatOptions), DateTimeFormat>> =
+        RefCell::new(HashMap::new());
+    static RELATIVE_TIME_FORMATS: RefCell<HashMap<(Option<String>, RelativeTimeFormatOptions), RelativeTimeFormat>> =
+        RefCell::new(HashMap::new());
+}
+
+impl SystemTime {
+    /// Formats this time for the given locale with `Intl.DateTimeFormat`.
+    ///
+    /// `locale` is a BCP 47 language tag such as `en-US`, or `None` for the
+    /// host's default locale.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the locale or the time zone is not valid, or if
+    /// this time is out of the range of JS `Date`.
+    pub fn format_locale(
+        &self,
+        locale: Option<&str>,
+        options: &DateTimeFormatOptions,
+    ) -> Result<String, IntlError> {
+        DATE_TIME_FORMATS.with(|formats| {
+            let mut formats = formats.borrow_mut();
+            let key = (locale.map(str::to_owned), options.clone());
+            let format = match formats.entry(key) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(
+                    DateTimeFormat::new(&locales(locale), &options.to_js())
+                        .map_err(IntlError::from_js)?,
+                ),
+            };
+            format
+                .format(self.to_js_millis())
+                .map_err(IntlError::from_js)
+        })
+    }
+
+    /// Formats the difference between this time and now for the given locale
+    /// with `Intl.RelativeTimeFormat`, like `3 minutes ago` or `in 2 hours`.
+    ///
+    /// See [`format_relative`] for how the unit is chosen.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the locale is not valid.
+    pub fn format_relative(
+        &self,
+        locale: Option<&str>,
+        options: RelativeTimeFormatOptions,
+    ) -> Result<String, IntlError> {
+        match SystemTime::now().duration_since(*self) {
+            Ok(elapsed) => format_relative_signed(elapsed, true, locale, options),
+            Err(err) => format_relative_signed(err.duration(), false, locale, options),
+        }
+    }
+}
+
+/// Formats a duration in the past for the given locale with
+/// `Intl.RelativeTimeFormat`, like `3 minutes ago`.
+///
+/// This is meant for durations such as the one returned by
+/// [`Instant::elapsed`](super::Instant::elapsed). Use
+/// [`SystemTime::format_relative`] for times that may be in the future.
+///
+/// The largest unit from seconds up to years in which the duration is at least
+/// 1 is used, and the value is rounded down, so 90 seconds are `1 minute ago`.
+/// Months are 30.44 days and years 365.25 days long.
+///
+/// # Errors
+///
+/// Returns an error if the locale is not valid.
+pub fn format_relative(
+    elapsed: Duration,
+    locale: Option<&str>,
+    options: RelativeTimeFormatOptions,
+) -> Result<String, IntlError> {
+    format_relative_signed(elapsed, true, locale, options)
+}
+
+fn format_relative_signed(
+    duration: Duration,
+    past: bool,
+    locale: Option<&str>,
+    options: RelativeTimeFormatOptions,
+) -> Result<String, IntlError> {
+    let (value, unit) = relative_unit(duration);
+    let value = if past { -value } else { value };
+    RELATIVE_TIME_FORMATS.with(|formats| {
+        let mut formats = formats.borrow_mut();
+        let key = (locale.map(str::to_owned), options);
+        let format = match formats.entry(key) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(
+                RelativeTimeFormat::new(&locales(locale), &options.to_js())
+                    .map_err(IntlError::from_js)?,
+            ),
+        };
+        Ok(format.format(value, unit))
+    })
+}
+
+/// Returns the number of whole units in `duration` and the name of the unit,
+/// as taken by `Intl.RelativeTimeFormat`.
+fn relative_unit(duration: Duration) -> (f64, &'static str) {
+    const UNITS: [(u64, &str); 6] = [
+        (31_557_600, "year"),
+        (2_629_800, "month"),
+        (604_800, "week"),
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+    ];
+    let secs = duration.as_secs();
+    for (unit_secs, unit) in UNITS {
+        if secs >= unit_secs {
+            return ((secs / unit_secs) as f64, unit);
+        }
+    }
+    (secs as f64, "second")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_units() {
+        assert_eq!(relative_unit(Duration::ZERO), (0.0, "second"));
+        assert_eq!(
+            relative_unit(Duration::from_millis(59_999)),
+            (59.0, "second")
+        );
+        assert_eq!(relative_unit(Duration::from_secs(90)), (1.0, "minute"));
+        assert_eq!(relative_unit(Duration::from_secs(7_200)), (2.0, "hour"));
+        assert_eq!(relative_unit(Duration::from_secs(86_400 * 6)), (6.0, "day"));
+        assert_eq!(
+            relative_unit(Duration::from_secs(86_400 * 29)),
+            (4.0, "week")
+        );
+        assert_eq!(
+            relative_unit(Duration::from_secs(86_400 * 31)),
+            (1.0, "month")
+        );
+        assert_eq!(
+            relative_unit(Duration::from_secs(86_400 * 365)),
+            (11.0, "month")
+        );
+        assert_eq!(
+            relative_unit(Duration::from_secs(86_400 * 800)),
+            (2.0, "year")
+        );
+    }
+}
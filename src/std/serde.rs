@@ -0,0 +1,497 @@
+//! Alternative serde encodings for [`SystemTime`] and `Duration`, enabled by
+//! the `serde` feature.
+//!
+//! The modules in here are meant for `#[serde(with = "...")]` attributes.
+//! Each has an `option` submodule for `Option` fields:
+//!
+//! - [`unix_millis`] encodes a `SystemTime` as an integer number of
+//!   milliseconds since `UNIX_EPOCH`, like `Date.now()`.
+//! - [`unix_secs_f64`] encodes a `SystemTime` as a fractional number of
+//!   seconds since `UNIX_EPOCH`.
+//! - [`rfc3339`] encodes a `SystemTime` as an RFC 3339 string.
+//! - [`duration_millis`] encodes a `Duration` as an integer number of
+//!   milliseconds.
+//! - [`duration_millis_f64`] encodes a `Duration` as a fractional number of
+//!   milliseconds, as taken by `setTimeout`.
+//!
+//! The numeric encodings accept both integers and floats when deserializing,
+//! so values that went through a JS `number` can be read back.
+
+use serde_crate::de::{self, Deserialize, Deserializer, Visitor};
+use serde_crate::ser::{self, Serialize, Serializer};
+use std::fmt;
+use std::time::Duration;
+
+use super::interop::duration_from_js_millis;
+use super::{SystemTime, NANOS_PER_SEC};
+
+const MILLIS_PER_SEC: i128 = 1_000;
+const NANOS_PER_MILLI: u32 = 1_000_000;
+
+/// Milliseconds since `UNIX_EPOCH`, as an integer.
+///
+/// Times are rounded down to the millisecond when serializing. Floats are
+/// accepted when deserializing and rounded to the nearest nanosecond.
+pub mod unix_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        UnixMillis(*time).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        UnixMillis::deserialize(deserializer).map(|time| time.0)
+    }
+
+    /// Like [`unix_millis`], for `Option<SystemTime>`.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            time: &Option<SystemTime>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            time.map(UnixMillis).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<SystemTime>, D::Error> {
+            Ok(Option::<UnixMillis>::deserialize(deserializer)?.map(|time| time.0))
+        }
+    }
+}
+
+/// Seconds since `UNIX_EPOCH`, as a float.
+///
+/// An `f64` has about 15 significant digits, so current times keep
+/// microsecond precision.
+pub mod unix_secs_f64 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        UnixSecsF64(*time).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        UnixSecsF64::deserialize(deserializer).map(|time| time.0)
+    }
+
+    /// Like [`unix_secs_f64`], for `Option<SystemTime>`.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            time: &Option<SystemTime>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            time.map(UnixSecsF64).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<SystemTime>, D::Error> {
+            Ok(Option::<UnixSecsF64>::deserialize(deserializer)?.map(|time| time.0))
+        }
+    }
+}
+
+/// An RFC 3339 string, as written by [`SystemTime::to_rfc3339`] and read by
+/// [`SystemTime::parse_rfc3339`].
+pub mod rfc3339 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        Rfc3339(*time).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        Rfc3339::deserialize(deserializer).map(|time| time.0)
+    }
+
+    /// Like [`rfc3339`], for `Option<SystemTime>`.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            time: &Option<SystemTime>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            time.map(Rfc3339).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<SystemTime>, D::Error> {
+            Ok(Option::<Rfc3339>::deserialize(deserializer)?.map(|time| time.0))
+        }
+    }
+}
+
+/// Milliseconds, as an integer.
+///
+/// Durations are rounded down to the millisecond when serializing. Floats are
+/// accepted when deserializing and rounded to the nearest nanosecond.
+pub mod duration_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        DurationMillis(*duration).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        DurationMillis::deserialize(deserializer).map(|duration| duration.0)
+    }
+
+    /// Like [`duration_millis`], for `Option<Duration>`.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            duration: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            duration.map(DurationMillis).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            Ok(Option::<DurationMillis>::deserialize(deserializer)?.map(|duration| duration.0))
+        }
+    }
+}
+
+/// Milliseconds, as a float.
+///
+/// This is the encoding of
+/// [`duration_to_js_millis`](crate::std::duration_to_js_millis).
+pub mod duration_millis_f64 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        DurationMillisF64(*duration).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        DurationMillisF64::deserialize(deserializer).map(|duration| duration.0)
+    }
+
+    /// Like [`duration_millis_f64`], for `Option<Duration>`.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            duration: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            duration.map(DurationMillisF64).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            Ok(Option::<DurationMillisF64>::deserialize(deserializer)?.map(|duration| duration.0))
+        }
+    }
+}
+
+struct UnixMillis(SystemTime);
+
+impl Serialize for UnixMillis {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let time = self.0;
+        let millis =
+            i128::from(time.secs) * MILLIS_PER_SEC + i128::from(time.nanos / NANOS_PER_MILLI);
+        let millis = i64::try_from(millis)
+            .map_err(|_| ser::Error::custom("system time is out of range for unix milliseconds"))?;
+        serializer.serialize_i64(millis)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnixMillis {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_i64(NumberVisitor("milliseconds since UNIX_EPOCH"))
+            .and_then(|number| match number {
+                Number::Int(millis) => Ok(UnixMillis(SystemTime {
+                    secs: millis.div_euclid(MILLIS_PER_SEC) as i64,
+                    nanos: millis.rem_euclid(MILLIS_PER_SEC) as u32 * NANOS_PER_MILLI,
+                })),
+                Number::Float(millis) => from_js_millis(millis).map(UnixMillis),
+            })
+    }
+}
+
+struct UnixSecsF64(SystemTime);
+
+impl Serialize for UnixSecsF64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let time = self.0;
+        serializer.serialize_f64(time.secs as f64 + f64::from(time.nanos) / NANOS_PER_SEC as f64)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnixSecsF64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_f64(NumberVisitor("seconds since UNIX_EPOCH"))
+            .and_then(|number| match number {
+                Number::Int(secs) => i64::try_from(secs)
+                    .map(|secs| UnixSecsF64(SystemTime { secs, nanos: 0 }))
+                    .map_err(|_| de::Error::custom("time value is out of range for SystemTime")),
+                Number::Float(secs) => from_js_millis(secs * 1_000.0).map(UnixSecsF64),
+            })
+    }
+}
+
+struct Rfc3339(SystemTime);
+
+impl Serialize for Rfc3339 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let time = self.0.to_rfc3339().map_err(ser::Error::custom)?;
+        serializer.serialize_str(&time)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rfc3339 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Rfc3339Visitor;
+
+        impl Visitor<'_> for Rfc3339Visitor {
+            type Value = Rfc3339;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an RFC 3339 timestamp")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Rfc3339, E> {
+                SystemTime::parse_rfc3339(value)
+                    .map(Rfc3339)
+                    .map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(Rfc3339Visitor)
+    }
+}
+
+struct DurationMillis(Duration);
+
+impl Serialize for DurationMillis {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let millis = u64::try_from(self.0.as_millis())
+            .map_err(|_| ser::Error::custom("duration is out of range for milliseconds"))?;
+        serializer.serialize_u64(millis)
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationMillis {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_u64(NumberVisitor("a duration in milliseconds"))
+            .and_then(duration_from_number)
+            .map(DurationMillis)
+    }
+}
+
+struct DurationMillisF64(Duration);
+
+impl Serialize for DurationMillisF64 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(super::duration_to_js_millis(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationMillisF64 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_f64(NumberVisitor("a duration in milliseconds"))
+            .and_then(duration_from_number)
+            .map(DurationMillisF64)
+    }
+}
+
+/// A number read by [`NumberVisitor`]. Integers are kept apart so that they
+/// are converted without rounding.
+enum Number {
+    Int(i128),
+    Float(f64),
+}
+
+/// Accepts any integer or float, so that values are read back whatever number
+/// type the format or the other side used.
+struct NumberVisitor(&'static str);
+
+impl Visitor<'_> for NumberVisitor {
+    type Value = Number;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(self.0)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Number, E> {
+        Ok(Number::Int(value.into()))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Number, E> {
+        Ok(Number::Int(value.into()))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Number, E> {
+        Ok(Number::Float(value))
+    }
+}
+
+fn from_js_millis<E: de::Error>(millis: f64) -> Result<SystemTime, E> {
+    SystemTime::try_from_js_millis(millis).map_err(E::custom)
+}
+
+fn duration_from_number<E: de::Error>(number: Number) -> Result<Duration, E> {
+    match number {
+        Number::Int(millis) => u64::try_from(millis)
+            .map(Duration::from_millis)
+            .map_err(|_| E::custom("duration is negative")),
+        Number::Float(millis) => duration_from_js_millis(millis).map_err(E::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::UNIX_EPOCH;
+
+    fn to_json<T: Serialize>(value: T) -> String {
+        serde_json::to_string(&value).unwrap()
+    }
+
+    fn from_json<'de, T: Deserialize<'de>>(json: &'de str) -> Result<T, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn unix_millis() {
+        let t = UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        assert_eq!(to_json(UnixMillis(t)), "1700000000123");
+        assert_eq!(
+            from_json::<UnixMillis>("1700000000123").unwrap().0,
+            UNIX_EPOCH + Duration::from_millis(1_700_000_000_123)
+        );
+        assert_eq!(
+            from_json::<UnixMillis>("1700000000123.5").unwrap().0,
+            UNIX_EPOCH + Duration::new(1_700_000_000, 123_500_000)
+        );
+
+        let before = UNIX_EPOCH - Duration::from_micros(1500);
+        assert_eq!(to_json(UnixMillis(before)), "-2");
+        assert_eq!(
+            from_json::<UnixMillis>("-2").unwrap().0,
+            UNIX_EPOCH - Duration::from_millis(2)
+        );
+
+        assert!(from_json::<UnixMillis>("\"1700000000123\"").is_err());
+        assert!(from_json::<UnixMillis>("1e300").is_err());
+        let max = SystemTime {
+            secs: i64::MAX,
+            nanos: 0,
+        };
+        assert!(serde_json::to_string(&UnixMillis(max)).is_err());
+    }
+
+    #[test]
+    fn unix_secs_f64() {
+        let t = UNIX_EPOCH + Duration::from_millis(1_700_000_000_500);
+        assert_eq!(to_json(UnixSecsF64(t)), "1700000000.5");
+        assert_eq!(from_json::<UnixSecsF64>("1700000000.5").unwrap().0, t);
+        assert_eq!(
+            from_json::<UnixSecsF64>("-1").unwrap().0,
+            UNIX_EPOCH - Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn rfc3339() {
+        let t = UNIX_EPOCH + Duration::new(1_700_000_000, 123_000_000);
+        assert_eq!(to_json(Rfc3339(t)), r#""2023-11-14T22:13:20.123Z""#);
+        assert_eq!(
+            from_json::<Rfc3339>(r#""2023-11-14T23:13:20.123+01:00""#)
+                .unwrap()
+                .0,
+            t
+        );
+        assert!(from_json::<Rfc3339>(r#""2023-11-14""#).is_err());
+        assert!(from_json::<Rfc3339>("1700000000").is_err());
+
+        // Only years from 0000 to 9999 can be written.
+        let last = UNIX_EPOCH + Duration::new(253_402_300_799, 999_999_999);
+        let serialized = to_json(Rfc3339(last));
+        assert_eq!(serialized, r#""9999-12-31T23:59:59.999999999Z""#);
+        assert_eq!(from_json::<Rfc3339>(&serialized).unwrap().0, last);
+        let first = UNIX_EPOCH - Duration::from_secs(62_167_219_200);
+        let serialized = to_json(Rfc3339(first));
+        assert_eq!(serialized, r#""0000-01-01T00:00:00Z""#);
+        assert_eq!(from_json::<Rfc3339>(&serialized).unwrap().0, first);
+        assert!(serde_json::to_string(&Rfc3339(last + Duration::from_nanos(1))).is_err());
+        assert!(serde_json::to_string(&Rfc3339(first - Duration::from_nanos(1))).is_err());
+    }
+
+    #[test]
+    fn duration_millis() {
+        assert_eq!(
+            to_json(DurationMillis(Duration::new(1, 500_999_999))),
+            "1500"
+        );
+        assert_eq!(
+            from_json::<DurationMillis>("1500").unwrap().0,
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            from_json::<DurationMillis>("0.25").unwrap().0,
+            Duration::from_micros(250)
+        );
+        assert!(from_json::<DurationMillis>("-1").is_err());
+        assert!(from_json::<DurationMillis>("-0.5").is_err());
+
+        assert_eq!(
+            to_json(DurationMillisF64(Duration::from_micros(1500))),
+            "1.5"
+        );
+        assert_eq!(
+            from_json::<DurationMillisF64>("1.5").unwrap().0,
+            Duration::from_micros(1500)
+        );
+        assert_eq!(
+            from_json::<DurationMillisF64>("2").unwrap().0,
+            Duration::from_millis(2)
+        );
+    }
+
+    #[test]
+    fn with_functions() {
+        let t = UNIX_EPOCH + Duration::from_millis(1500);
+
+        let mut json = Vec::new();
+        super::unix_millis::serialize(&t, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        assert_eq!(json, b"1500");
+        let mut deserializer = serde_json::Deserializer::from_str("1500");
+        assert_eq!(
+            super::unix_millis::deserialize(&mut deserializer).unwrap(),
+            t
+        );
+
+        let mut json = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut json);
+        super::rfc3339::option::serialize(&None, &mut serializer).unwrap();
+        assert_eq!(json, b"null");
+        let mut deserializer = serde_json::Deserializer::from_str(r#""1970-01-01T00:00:01.5Z""#);
+        assert_eq!(
+            super::rfc3339::option::deserialize(&mut deserializer).unwrap(),
+            Some(t)
+        );
+        let mut deserializer = serde_json::Deserializer::from_str("null");
+        assert_eq!(
+            super::duration_millis::option::deserialize(&mut deserializer).unwrap(),
+            None
+        );
+    }
+}
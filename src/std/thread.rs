@@ -0,0 +1,108 @@
+//! A replacement for `std::thread::sleep`, which panics on JS hosts.
+//!
+//! Blocking is done with `Atomics.wait`, which is allowed in Web Workers and
+//! on Node.js when `SharedArrayBuffer` is available. Browsers only provide
+//! `SharedArrayBuffer` on cross-origin isolated pages, and do not allow
+//! `Atomics.wait` on the main thread at all.
+//!
+//! On WASI targets the standard library can block, so both functions call
+//! `std::thread::sleep` there.
+
+#[cfg(not(target_os = "wasi"))]
+use js_sys::{Atomics, Int32Array, Reflect, SharedArrayBuffer};
+use std::fmt;
+use std::time::Duration;
+#[cfg(not(target_os = "wasi"))]
+use wasm_bindgen::JsValue;
+
+#[cfg(not(target_os = "wasi"))]
+use super::{duration_to_js_millis, Instant};
+
+/// Error returned by [`try_sleep`] when the current thread is not allowed to
+/// block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepError(&'static str);
+
+impl fmt::Display for SleepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for SleepError {}
+
+#[cfg(not(target_os = "wasi"))]
+thread_local! {
+    /// A cell that is never notified, for `Atomics.wait` to wait on until its
+    /// timeout expires.
+    static WAIT_CELL: Option<Int32Array> = {
+        let global = js_sys::global();
+        if Reflect::has(&global, &JsValue::from_str("SharedArrayBuffer")).unwrap_or(false) {
+            Some(Int32Array::new(&SharedArrayBuffer::new(4)))
+        } else {
+            None
+        }
+    };
+}
+
+/// Puts the current thread to sleep for at least the given duration, like
+/// `std::thread::sleep`.
+///
+/// Where `Atomics.wait` is not allowed, such as on the main thread of a
+/// browser, this falls back to busy-waiting on [`Instant::now`]. That keeps
+/// ported code working, but burns CPU time and freezes the page for the whole
+/// duration, so prefer [`try_sleep`] or an async timer there.
+///
+/// Like `std::thread::sleep`, this ignores the paused clock of
+/// `tokio-test-util`.
+#[cfg(not(target_os = "wasi"))]
+pub fn sleep(dur: Duration) {
+    if try_sleep(dur).is_err() {
+        let deadline = Instant::now_unmocked().checked_add(dur);
+        while deadline.is_none_or(|deadline| Instant::now_unmocked() < deadline) {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+/// Puts the current thread to sleep for at least the given duration with
+/// `Atomics.wait`.
+///
+/// # Errors
+///
+/// Returns an error without sleeping if `SharedArrayBuffer` is not available
+/// or if `Atomics.wait` is not allowed on the current thread.
+#[cfg(not(target_os = "wasi"))]
+pub fn try_sleep(dur: Duration) -> Result<(), SleepError> {
+    WAIT_CELL.with(|cell| {
+        let cell = cell.as_ref().ok_or(SleepError(
+            "`SharedArrayBuffer` is not available, the page must be cross-origin isolated",
+        ))?;
+        let deadline = Instant::now_unmocked().checked_add(dur);
+        let mut remaining = dur;
+        loop {
+            Atomics::wait_with_timeout(cell, 0, 0, duration_to_js_millis(remaining))
+                .map_err(|_| SleepError("`Atomics.wait` is not allowed on this thread"))?;
+            // Hosts may round the timeout down, so wait again for the rest.
+            let Some(deadline) = deadline else {
+                continue;
+            };
+            let now = Instant::now_unmocked();
+            if now >= deadline {
+                return Ok(());
+            }
+            remaining = deadline - now;
+        }
+    })
+}
+
+#[cfg(target_os = "wasi")]
+pub fn sleep(dur: Duration) {
+    std::thread::sleep(dur);
+}
+
+#[cfg(target_os = "wasi")]
+pub fn try_sleep(dur: Duration) -> Result<(), SleepError> {
+    std::thread::sleep(dur);
+    Ok(())
+}
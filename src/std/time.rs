@@ -0,0 +1,78 @@
+//! Conversions between [`SystemTime`] and `time` date-times, enabled by the
+//! `time` feature.
+
+use std::time::Duration;
+use time_crate::OffsetDateTime;
+
+use super::{OutOfRangeError, SystemTime};
+
+/// Returns the current time as an `OffsetDateTime` in UTC.
+///
+/// Unlike `time::OffsetDateTime::now_utc`, this reads the installed
+/// [`ClockSource`](super::ClockSource).
+///
+/// # Panics
+///
+/// Panics if the clock source returns a time that `time` can not represent.
+pub fn now_utc() -> OffsetDateTime {
+    OffsetDateTime::try_from(SystemTime::now()).expect("current time is out of range for time")
+}
+
+impl From<OffsetDateTime> for SystemTime {
+    fn from(date_time: OffsetDateTime) -> SystemTime {
+        SystemTime {
+            secs: date_time.unix_timestamp(),
+            nanos: 0,
+        } + Duration::from_nanos(u64::from(date_time.nanosecond()))
+    }
+}
+
+impl TryFrom<SystemTime> for OffsetDateTime {
+    type Error = OutOfRangeError;
+
+    fn try_from(time: SystemTime) -> Result<OffsetDateTime, OutOfRangeError> {
+        let nanos = i128::from(time.secs) * 1_000_000_000 + i128::from(time.nanos);
+        OffsetDateTime::from_unix_timestamp_nanos(nanos).map_err(|_| OutOfRangeError(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::std::UNIX_EPOCH;
+    use time_crate::UtcOffset;
+
+    #[test]
+    fn round_trip() {
+        let date_time =
+            OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_456_789).unwrap();
+        let time = SystemTime::from(date_time);
+        assert_eq!(time, UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789));
+        assert_eq!(OffsetDateTime::try_from(time), Ok(date_time));
+
+        let before_epoch = UNIX_EPOCH - Duration::new(14_182_940, 500);
+        let date_time = OffsetDateTime::try_from(before_epoch).unwrap();
+        assert_eq!(date_time.unix_timestamp(), -14_182_941);
+        assert_eq!(SystemTime::from(date_time), before_epoch);
+    }
+
+    #[test]
+    fn offset() {
+        let date_time = OffsetDateTime::from_unix_timestamp(1_700_000_000)
+            .unwrap()
+            .to_offset(UtcOffset::from_hms(5, 0, 0).unwrap());
+        assert_eq!(
+            SystemTime::from(date_time),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let far_future = UNIX_EPOCH + Duration::from_secs(i64::MAX as u64);
+        assert_eq!(
+            OffsetDateTime::try_from(far_future),
+            Err(OutOfRangeError(()))
+        );
+    }
+}
@@ -52,7 +52,7 @@ impl Clock {
     }
 
     pub(crate) fn new(start_paused: bool) -> Clock {
-        let now = Instant::now_js();
+        let now = Instant::now_unmocked();
 
         let clock = Clock {
             inner: Arc::new(Inner {
@@ -74,7 +74,7 @@ impl Clock {
         }
 
         let mut unforzen = self.inner.unfrozen.lock().unwrap();
-        (*unforzen) = Some(Instant::now_js());
+        (*unforzen) = Some(Instant::now_unmocked());
     }
 
     pub(crate) fn paused(&self) -> bool {
@@ -89,7 +89,7 @@ impl Clock {
             .lock()
             .unwrap()
             .expect("time is already frozen");
-        let elapsed = Instant::now_js() - unfrozen;
+        let elapsed = Instant::now_unmocked() - unfrozen;
         let mut base = self.inner.base.lock().unwrap();
         (*base) += elapsed;
         let mut unfrozen = self.inner.unfrozen.lock().unwrap();
@@ -113,7 +113,7 @@ impl Clock {
         let unfrozen = self.inner.unfrozen.lock().unwrap();
 
         if let Some(unfrozen) = *unfrozen {
-            ret += Instant::now_js() - unfrozen;
+            ret += Instant::now_unmocked() - unfrozen;
         }
 
         ret
@@ -1,93 +1,315 @@
+//! The global timer on JS hosts, driven by callbacks from the JS event loop.
+//!
+//! At most one callback of each kind is pending at any time: a re-poll after
+//! the timers were updated, and a timeout for the next timer that is due. The
+//! timeout is cleared and armed again when an earlier timer is added. Both
+//! kinds call closures that are created once per thread and reused.
+//!
+//! Immediate timeouts can not be cancelled, so the timeout callback is bound
+//! to a generation and ignored once it was replaced.
+
 use futures::task::{self, ArcWake};
+use js_sys::Function;
 use parking_lot::Mutex;
-use std::convert::TryFrom;
+use std::cell::{Cell, RefCell};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::task::Context;
-use std::time::Duration;
-use wasm_bindgen::{closure::Closure, JsCast};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 
-use crate::js::set_timeout;
+use super::scheduler::{self, Scheduled, Wake, MAX_DELAY};
 use crate::std::Instant;
 use crate::timer::{Timer, TimerHandle};
 
-/// Starts a background task, creates a `Timer`, and returns a handle to it.
+static TIMER: OnceLock<Mutex<Timer>> = OnceLock::new();
+
+fn timer() -> &'static Mutex<Timer> {
+    TIMER.get_or_init(|| Mutex::new(Timer::new()))
+}
+
+/// Creates the global `Timer`, starts driving it, and returns a handle to it.
 ///
 /// > **Note**: Contrary to the original `futures-timer` crate, we don't have
 /// >           any `forget()` method, as the task is automatically considered
 /// >           as "forgotten".
 pub(crate) fn run() -> TimerHandle {
-    let timer = Timer::new();
-    let handle = timer.handle();
-    schedule_callback(Arc::new(Mutex::new(timer)), Duration::new(0, 0));
+    let handle = timer().lock().handle();
+    wake();
     handle
 }
 
-/// Calls `Window::setTimeout` with the given `Duration`. The callback wakes up the timer and
-/// processes everything.
-fn schedule_callback(timer: Arc<Mutex<Timer>>, when: Duration) {
-    let cb = move || {
-        let mut timer_lock = timer.lock();
-
-        // We start by polling the timer. If any new `Delay` is created, the waker will be used
-        // to wake up this task pre-emptively. As such, we pass a `Waker` that calls
-        // `schedule_callback` with a delay of `0`.
-        let waker = task::waker(Arc::new(Waker {
-            timer: timer.clone(),
-        }));
-        let _ = Future::poll(Pin::new(&mut *timer_lock), &mut Context::from_waker(&waker));
-
-        // Notify the timers that are ready.
-        let now = Instant::now();
-        timer_lock.advance_to(now);
-
-        // A previous version of this function recursed in such a way as the strong count increased
-        // From testing, this one doesn't (it remains at a constant 2)
-        if Arc::strong_count(&timer) > 20 {
-            return;
+/// The pending JS callbacks of the current thread.
+struct Driver {
+    repoll: Closure<dyn FnMut()>,
+    fire: Closure<dyn FnMut(u32)>,
+    repoll_pending: Cell<bool>,
+    timeout: RefCell<Option<Timeout>>,
+    /// The generation of the latest armed timeout.
+    generation: Cell<u32>,
+    #[cfg(test)]
+    counts: Counts,
+}
+
+/// How often the driver scheduled, cleared and ran its timeout.
+#[cfg(test)]
+#[derive(Default)]
+struct Counts {
+    scheduled: Cell<usize>,
+    cleared: Cell<usize>,
+    fired: Cell<usize>,
+}
+
+struct Timeout {
+    /// When the callback runs, never earlier than when it was scheduled.
+    at: Instant,
+    generation: u32,
+    scheduled: Scheduled,
+}
+
+thread_local! {
+    static DRIVER: Driver = Driver {
+        repoll: Closure::new(|| turn(false)),
+        fire: Closure::new(fire),
+        repoll_pending: Cell::new(false),
+        timeout: RefCell::new(None),
+        generation: Cell::new(0),
+        #[cfg(test)]
+        counts: Counts::default(),
+    };
+}
+
+/// Makes the driver pick up updated timers, unless it is about to anyway.
+fn wake() {
+    // A paused clock fires the timers right away.
+    #[cfg(feature = "tokio-test-util")]
+    if super::clock::clock().paused() {
+        turn(true);
+        return;
+    }
+
+    DRIVER.with(|driver| {
+        if !driver.repoll_pending.replace(true) {
+            scheduler::schedule(Wake::Repoll, driver.repoll.as_ref().unchecked_ref());
         }
+    });
+}
 
-        // We call `schedule_callback` again for the next event.
-        let sleep_dur = timer_lock.next_event().map(|next_event| {
-            if next_event > now {
-                next_event - now
-            } else {
-                Duration::new(0, 0)
-            }
-        });
-        drop(timer_lock);
+/// Runs the driver for the timeout of the given generation, unless it was
+/// cleared or replaced since.
+fn fire(generation: u32) {
+    let current = DRIVER.with(|driver| {
+        driver
+            .timeout
+            .borrow()
+            .as_ref()
+            .is_some_and(|timeout| timeout.generation == generation)
+    });
+    if current {
+        turn(true);
+    }
+}
 
-        if let Some(sleep) = sleep_dur {
-            schedule_callback(timer, sleep);
+/// Processes the pending timer updates, fires the timers that are due if
+/// `fire` is set, and arms the timeout for the next timer.
+fn turn(fire: bool) {
+    DRIVER.with(|driver| {
+        if fire {
+            driver.timeout.borrow_mut().take();
+            #[cfg(test)]
+            driver.counts.fired.set(driver.counts.fired.get() + 1);
+        } else {
+            driver.repoll_pending.set(false);
         }
-    };
+    });
+
+    let mut timer = timer().lock();
 
+    // New timers wake this task, which re-polls the timer to pick them up.
+    let waker = task::waker(Arc::new(Waker));
+    let _ = Future::poll(Pin::new(&mut *timer), &mut Context::from_waker(&waker));
+
+    // Notify the timers that are ready. This is skipped when re-polling,
+    // which may run as a microtask, as the woken tasks could register new
+    // timers that are due right away and keep the event loop from ever
+    // running again.
+    let now = Instant::now();
+    if fire {
+        timer.advance_to(now);
+    }
+    let next_event = timer.next_event();
+    drop(timer);
+
+    // Nothing but `advance` moves a paused clock, and it wakes the driver.
     #[cfg(feature = "tokio-test-util")]
     if super::clock::clock().paused() {
-        cb();
-    } else {
-        let _ = set_timeout(
-            Closure::once_into_js(cb).unchecked_ref(),
-            i32::try_from(when.as_millis()).unwrap_or(0),
-        )
-        .unwrap();
+        return;
     }
 
-    #[cfg(not(feature = "tokio-test-util"))]
-    let _ = set_timeout(
-        Closure::once_into_js(cb).unchecked_ref(),
-        i32::try_from(when.as_millis()).unwrap_or(0),
-    )
-    .unwrap();
+    arm(next_event, now);
 }
 
-struct Waker {
-    timer: Arc<Mutex<Timer>>,
+/// Makes sure that the driver runs at `next_event`, replacing a pending
+/// timeout that runs later, and clearing it if there is no timer left.
+///
+/// Waits longer than `setTimeout` allows are split up. The driver then runs
+/// early, finds that no timer is due and arms the timeout again.
+fn arm(next_event: Option<Instant>, now: Instant) {
+    DRIVER.with(|driver| {
+        let mut timeout = driver.timeout.borrow_mut();
+        let Some(next_event) = next_event else {
+            if let Some(timeout) = timeout.take() {
+                timeout.scheduled.cancel();
+                #[cfg(test)]
+                driver.counts.cleared.set(driver.counts.cleared.get() + 1);
+            }
+            return;
+        };
+
+        let at = next_event.max(now);
+        let at = now.checked_add(MAX_DELAY).map_or(at, |limit| at.min(limit));
+        if timeout.as_ref().is_some_and(|timeout| timeout.at <= at) {
+            return;
+        }
+        if let Some(timeout) = timeout.take() {
+            timeout.scheduled.cancel();
+            #[cfg(test)]
+            driver.counts.cleared.set(driver.counts.cleared.get() + 1);
+        }
+        let generation = driver.generation.get().wrapping_add(1);
+        driver.generation.set(generation);
+        let callback: Function = driver
+            .fire
+            .as_ref()
+            .unchecked_ref::<Function>()
+            .bind1(&JsValue::UNDEFINED, &JsValue::from(generation))
+            .unchecked_into();
+        let scheduled = scheduler::schedule(Wake::Timeout(at - now), &callback);
+        #[cfg(test)]
+        driver
+            .counts
+            .scheduled
+            .set(driver.counts.scheduled.get() + 1);
+        *timeout = Some(Timeout {
+            at,
+            generation,
+            scheduled,
+        });
+    });
 }
 
+struct Waker;
+
 impl ArcWake for Waker {
-    fn wake_by_ref(arc_self: &Arc<Self>) {
-        schedule_callback(arc_self.timer.clone(), Duration::new(0, 0));
+    fn wake_by_ref(_arc_self: &Arc<Self>) {
+        wake();
+    }
+}
+
+#[cfg(all(test, target_family = "wasm"))]
+mod tests {
+    use super::*;
+    use futures::task::noop_waker_ref;
+    use std::time::Duration;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::tokio::sleep;
+
+    /// Returns how many timeouts were scheduled and cleared so far, and how
+    /// often the driver ran for one.
+    fn counts() -> (usize, usize, usize) {
+        DRIVER.with(|driver| {
+            (
+                driver.counts.scheduled.get(),
+                driver.counts.cleared.get(),
+                driver.counts.fired.get(),
+            )
+        })
+    }
+
+    /// Waits for a `setTimeout` of its own, so that the driver runs in the
+    /// meantime without being involved.
+    async fn wait(millis: i32) {
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            crate::js::set_timeout(&resolve, millis).unwrap();
+        });
+        JsFuture::from(promise).await.unwrap();
+    }
+
+    /// Lets the pending microtasks run, such as a re-poll of the driver, but
+    /// no other task.
+    async fn yield_now() {
+        let promise = js_sys::Promise::resolve(&wasm_bindgen::JsValue::UNDEFINED);
+        JsFuture::from(promise).await.unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    async fn earlier_timers_replace_the_timeout() {
+        let (scheduled, cleared, _) = counts();
+        let mut sleeps = Vec::new();
+        for secs in (1..=5).rev() {
+            sleeps.push(sleep(Duration::from_secs(secs * 60)));
+            wait(0).await;
+        }
+        // Each earlier deadline clears the previous timeout, so only one is
+        // pending at any time.
+        assert_eq!(counts().0 - scheduled, 5);
+        assert_eq!(counts().1 - cleared, 4);
+
+        // A later deadline does not touch the pending timeout.
+        sleeps.push(sleep(Duration::from_secs(10 * 60)));
+        wait(0).await;
+        assert_eq!(counts().0 - scheduled, 5);
+        assert_eq!(counts().1 - cleared, 4);
+
+        // The timeout is cleared once no timer is left.
+        drop(sleeps);
+        wait(0).await;
+        assert_eq!(counts().0 - scheduled, 5);
+        assert_eq!(counts().1 - cleared, 5);
+    }
+
+    #[wasm_bindgen_test]
+    async fn long_timers_do_not_wake_the_driver_early() {
+        let (scheduled, _, fired) = counts();
+        // Longer than `setTimeout` allows, which would otherwise overflow and
+        // run the timeout right away, over and over again.
+        let mut sleep = Box::pin(sleep(MAX_DELAY + Duration::from_secs(60 * 60)));
+        wait(50).await;
+
+        let mut cx = Context::from_waker(noop_waker_ref());
+        assert!(sleep.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(counts().0 - scheduled, 1);
+        assert_eq!(counts().2 - fired, 0);
+
+        drop(sleep);
+        wait(0).await;
+    }
+
+    #[wasm_bindgen_test]
+    async fn stale_immediate_timeouts_are_ignored() {
+        let (scheduled, cleared, fired) = counts();
+        // A timer that is due right away arms an immediate timeout, which can
+        // not be cancelled.
+        let due = sleep(Duration::ZERO);
+        yield_now().await;
+        drop(due);
+        yield_now().await;
+        // It is replaced by a `setTimeout` before it runs.
+        let later = sleep(Duration::from_secs(60));
+        yield_now().await;
+        assert_eq!(counts().0 - scheduled, 2);
+        assert_eq!(counts().1 - cleared, 1);
+
+        // The stale callback must neither take the place of the `setTimeout`
+        // nor arm another one.
+        wait(10).await;
+        assert_eq!(counts().0 - scheduled, 2);
+        assert_eq!(counts().2 - fired, 0);
+
+        drop(later);
+        wait(0).await;
+        assert_eq!(counts().1 - cleared, 2);
     }
 }
@@ -265,7 +265,7 @@ mod tests {
         for t in v {
             h.push(t);
         }
-        return h;
+        h
     }
 
     #[test]
@@ -247,39 +247,48 @@ use heap::{Heap, Slot};
 pub mod arc_list;
 #[cfg(feature = "tokio-test-util")]
 pub mod clock;
+#[cfg(not(target_os = "wasi"))]
 mod global;
 mod heap;
+#[cfg(not(target_os = "wasi"))]
+mod scheduler;
+#[cfg(target_os = "wasi")]
+pub(crate) mod wasi;
 
-/// A "timer heap" used to power separately owned instances of `Delay` and
-/// `Interval`.
+/// A "timer heap" used to power separately owned instances of `Sleep`,
+/// `Interval`, `Timeout` and `DelayQueue`.
 ///
 /// This timer is implemented as a priority queued-based heap. Each `Timer`
 /// contains a few primary methods which which to drive it:
 ///
-/// * `next_wake` indicates how long the ambient system needs to sleep until it
-///   invokes further processing on a `Timer`
+/// * `next_event` indicates when the ambient system needs to invoke further
+///   processing on a `Timer`
 /// * `advance_to` is what actually fires timers on the `Timer`, and should be
 ///   called essentially every iteration of the event loop, or when the time
-///   specified by `next_wake` has elapsed.
-/// * The `Future` implementation for `Timer` is used to process incoming timer
-///   updates and requests. This is used to schedule new timeouts, update
-///   existing ones, or delete existing timeouts. The `Future` implementation
-///   will never resolve, but it'll schedule notifications of when to wake up
-///   and process more messages.
+///   specified by `next_event` has elapsed.
+/// * The `Future` implementation for `Timer` never resolves, but wakes the
+///   task polling it whenever a timer is created, reset or dropped, so that
+///   it can check `next_event` again.
 ///
 /// Note that if you're using this crate you probably don't need to use a
-/// `Timer` as there is a global one already available for you run on a helper
-/// thread. If this isn't desirable, though, then the
-/// `TimerHandle::set_fallback` method can be used instead!
+/// `Timer` as there is a global one already available, driven by the JS event
+/// loop. A separate `Timer` is useful to control when timers fire, for example
+/// in tests, or to cancel a group of timers at once. Dropping a `Timer`
+/// cancels every timer created from its handles: they never complete.
+///
+/// Timers are bound to a `Timer` with constructors such as
+/// [`Sleep::new_handle`](crate::tokio::Sleep::new_handle), or to the global
+/// timer by default. [`TimerHandle::set_as_global_fallback`] replaces the
+/// global timer if it has not been used yet.
 pub struct Timer {
     inner: Arc<Inner>,
     timer_heap: Heap<HeapTimer>,
 }
 
-/// A handle to a `Timer` which is used to create instances of a `Delay`.
+/// A handle to a `Timer` which is used to create instances of a `Sleep`.
 #[derive(Clone)]
 pub struct TimerHandle {
-    pub inner: Weak<Inner>,
+    pub(crate) inner: Weak<Inner>,
 }
 
 pub struct Inner {
@@ -340,7 +349,13 @@ impl Timer {
     ///
     /// Event loops or threads typically want to sleep until the specified
     /// instant.
-    pub fn next_event(&self) -> Option<Instant> {
+    ///
+    /// This takes `&mut self` as it first applies the pending updates of the
+    /// timers, such as new, reset or dropped ones, which are otherwise only
+    /// picked up by polling the timer or calling `advance_to`. Without that,
+    /// the result would miss a timer that was just created.
+    pub fn next_event(&mut self) -> Option<Instant> {
+        self.process_updates();
         self.timer_heap.peek().map(|t| t.at)
     }
 
@@ -349,6 +364,7 @@ impl Timer {
     /// This method should be called on `Timer` periodically to advance the
     /// internal state and process any pending timers which need to fire.
     pub fn advance_to(&mut self, now: Instant) {
+        self.process_updates();
         loop {
             match self.timer_heap.peek() {
                 Some(head) if head.at <= now => {}
@@ -372,6 +388,19 @@ impl Timer {
         }
     }
 
+    /// Applies the timers that were created, reset or dropped since the last
+    /// call to the heap.
+    fn process_updates(&mut self) {
+        let mut list = self.inner.list.take();
+        while let Some(node) = list.pop() {
+            let at = *node.at.lock().unwrap();
+            match at {
+                Some(at) => self.update_or_add(at, node),
+                None => self.remove(node),
+            }
+        }
+    }
+
     /// Either updates the timer at slot `idx` to fire at `at`, or adds a new
     /// timer at `idx` and sets it to fire at `at`.
     fn update_or_add(&mut self, at: Instant, node: Arc<Node<ScheduledTimer>>) {
@@ -412,14 +441,7 @@ impl Future for Timer {
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         Pin::new(&mut self.inner).waker.register(cx.waker());
-        let mut list = self.inner.list.take();
-        while let Some(node) = list.pop() {
-            let at = *node.at.lock().unwrap();
-            match at {
-                Some(at) => self.update_or_add(at, node),
-                None => self.remove(node),
-            }
-        }
+        self.process_updates();
         Poll::Pending
     }
 }
@@ -443,6 +465,12 @@ impl Drop for Timer {
     }
 }
 
+impl Default for Timer {
+    fn default() -> Timer {
+        Timer::new()
+    }
+}
+
 impl fmt::Debug for Timer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         f.debug_struct("Timer").field("heap", &"...").finish()
@@ -512,6 +540,11 @@ impl TimerHandle {
         }
     }
 
+    /// Returns whether this is the handle returned by `TimerHandle::default`.
+    pub(crate) fn is_global(&self) -> bool {
+        std::ptr::eq(self.inner.as_ptr(), HANDLE_FALLBACK.load(SeqCst))
+    }
+
     fn into_raw(self) -> *mut Inner {
         self.inner.into_raw() as *mut Inner
     }
@@ -532,7 +565,10 @@ impl Default for TimerHandle {
         // handle which will return errors when timer objects are attempted to
         // be associated.
         if fallback == EMPTY_HANDLE {
+            #[cfg(not(target_os = "wasi"))]
             let handle = global::run();
+            #[cfg(target_os = "wasi")]
+            let handle = wasi::run();
 
             // If we successfully set ourselves as the actual fallback then we
             // want to `forget` the helper thread to ensure that it persists
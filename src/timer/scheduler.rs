@@ -0,0 +1,225 @@
+//! The JS APIs used to run the timer driver again.
+//!
+//! `setTimeout` is clamped to at least 4ms once calls are nested, which adds
+//! up quickly as every new `Sleep` wakes the driver. The driver therefore
+//! picks the cheapest API available on the host for each kind of wake-up.
+
+use js_sys::{Function, Object, Reflect};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::Duration;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use crate::js::{
+    clear_timeout, post_task, queue_microtask, set_timeout, MessageChannel, MessagePort,
+};
+
+/// Why the timer driver needs to run again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Wake {
+    /// The timers were updated. The driver only needs to pick up the updates
+    /// and schedule the next timed wake-up, no timer fires.
+    Repoll,
+    /// A timer is due after the given duration.
+    Timeout(Duration),
+}
+
+/// A pending call of a callback.
+pub(crate) struct Scheduled {
+    /// The ID returned by `setTimeout`, the only scheduler that can be
+    /// cancelled.
+    timeout_id: Option<JsValue>,
+}
+
+impl Scheduled {
+    /// Cancels the call if possible. Otherwise the callback still runs and
+    /// must cope with being called for nothing.
+    pub(crate) fn cancel(self) {
+        if let Some(timeout_id) = self.timeout_id {
+            let _ = clear_timeout(&timeout_id);
+        }
+    }
+}
+
+/// A way to call a function later.
+trait Scheduler {
+    fn schedule(&self, callback: &Function, delay: Duration) -> Scheduled;
+}
+
+/// Runs the callback before control returns to the event loop. Only suitable
+/// for work that does not wake tasks, or tasks could starve the event loop.
+struct QueueMicrotask;
+
+impl Scheduler for QueueMicrotask {
+    fn schedule(&self, callback: &Function, _delay: Duration) -> Scheduled {
+        queue_microtask(callback);
+        Scheduled { timeout_id: None }
+    }
+}
+
+/// Runs the callback in a new task as soon as possible, without the clamping
+/// of `setTimeout`.
+struct PostMessage {
+    port: MessagePort,
+    queue: Rc<RefCell<VecDeque<Function>>>,
+    keep_alive: Rc<KeepAlive>,
+    _on_message: Closure<dyn FnMut()>,
+}
+
+/// On Node.js, a port with a listener keeps the process running, so it is
+/// only referenced while messages are pending.
+struct KeepAlive(Option<(MessagePort, Function, Function)>);
+
+impl KeepAlive {
+    fn new(port: &MessagePort) -> KeepAlive {
+        let method = |name: &str| {
+            Reflect::get(port, &JsValue::from_str(name))
+                .ok()
+                .and_then(|method| method.dyn_into::<Function>().ok())
+        };
+        KeepAlive(
+            method("ref")
+                .zip(method("unref"))
+                .map(|(reference, unreference)| (port.clone(), reference, unreference)),
+        )
+    }
+
+    fn set(&self, alive: bool) {
+        if let Some((port, reference, unreference)) = &self.0 {
+            let method = if alive { reference } else { unreference };
+            let _ = method.call0(port);
+        }
+    }
+}
+
+impl PostMessage {
+    fn new() -> PostMessage {
+        let channel = MessageChannel::new();
+        let receiver = channel.port1();
+        let queue = Rc::new(RefCell::new(VecDeque::<Function>::new()));
+        let keep_alive = Rc::new(KeepAlive::new(&receiver));
+        let on_message = Closure::<dyn FnMut()>::new({
+            let queue = queue.clone();
+            let keep_alive = keep_alive.clone();
+            move || {
+                let (callback, empty) = {
+                    let mut queue = queue.borrow_mut();
+                    (queue.pop_front(), queue.is_empty())
+                };
+                if empty {
+                    keep_alive.set(false);
+                }
+                if let Some(callback) = callback {
+                    let _ = callback.call0(&JsValue::UNDEFINED);
+                }
+            }
+        });
+        receiver.set_onmessage(on_message.as_ref().unchecked_ref());
+        keep_alive.set(false);
+        PostMessage {
+            port: channel.port2(),
+            queue,
+            keep_alive,
+            _on_message: on_message,
+        }
+    }
+}
+
+impl Scheduler for PostMessage {
+    fn schedule(&self, callback: &Function, _delay: Duration) -> Scheduled {
+        let mut queue = self.queue.borrow_mut();
+        if queue.is_empty() {
+            self.keep_alive.set(true);
+        }
+        queue.push_back(callback.clone());
+        drop(queue);
+        self.port.post_message(&JsValue::NULL);
+        Scheduled { timeout_id: None }
+    }
+}
+
+/// `scheduler.postTask`, the Prioritized Task Scheduling API.
+struct PostTask;
+
+impl Scheduler for PostTask {
+    fn schedule(&self, callback: &Function, delay: Duration) -> Scheduled {
+        let options = Object::new();
+        let _ = Reflect::set(
+            &options,
+            &JsValue::from_str("delay"),
+            &JsValue::from_f64(delay.as_secs_f64() * 1000.0),
+        );
+        let _ = post_task(callback, &options);
+        Scheduled { timeout_id: None }
+    }
+}
+
+/// The longest delay that `setTimeout` takes, about 24.8 days. Longer delays
+/// overflow and run right away.
+pub(crate) const MAX_DELAY: Duration = Duration::from_millis(i32::MAX as u64);
+
+struct SetTimeout;
+
+impl Scheduler for SetTimeout {
+    fn schedule(&self, callback: &Function, delay: Duration) -> Scheduled {
+        // Rounded up, so that the driver does not wake before the deadline.
+        let millis = delay.as_nanos().div_ceil(1_000_000);
+        let millis = i32::try_from(millis).unwrap_or(i32::MAX);
+        let timeout_id = set_timeout(callback, millis).unwrap();
+        Scheduled {
+            timeout_id: Some(timeout_id),
+        }
+    }
+}
+
+/// The scheduler used for each kind of wake-up, the cheapest that the host
+/// provides.
+struct Schedulers {
+    repoll: Rc<dyn Scheduler>,
+    immediate: Rc<dyn Scheduler>,
+    delayed: Rc<dyn Scheduler>,
+}
+
+impl Schedulers {
+    fn detect() -> Schedulers {
+        let global = js_sys::global();
+        let has = |object: &JsValue, name: &str| {
+            Reflect::has(object, &JsValue::from_str(name)).unwrap_or(false)
+        };
+        let post_message = has(&global, "MessageChannel")
+            .then(|| Rc::new(PostMessage::new()) as Rc<dyn Scheduler>);
+        let post_task = Reflect::get(&global, &JsValue::from_str("scheduler"))
+            .ok()
+            .filter(|scheduler| scheduler.is_object() && has(scheduler, "postTask"))
+            .map(|_| Rc::new(PostTask) as Rc<dyn Scheduler>);
+        let set_timeout = Rc::new(SetTimeout) as Rc<dyn Scheduler>;
+
+        let immediate = post_message
+            .or(post_task)
+            .unwrap_or_else(|| set_timeout.clone());
+        let repoll = if has(&global, "queueMicrotask") {
+            Rc::new(QueueMicrotask)
+        } else {
+            immediate.clone()
+        };
+        Schedulers {
+            repoll,
+            immediate,
+            delayed: set_timeout,
+        }
+    }
+}
+
+thread_local! {
+    static SCHEDULERS: Schedulers = Schedulers::detect();
+}
+
+/// Calls `callback` for the given kind of wake-up.
+pub(crate) fn schedule(wake: Wake, callback: &Function) -> Scheduled {
+    SCHEDULERS.with(|schedulers| match wake {
+        Wake::Repoll => schedulers.repoll.schedule(callback, Duration::ZERO),
+        Wake::Timeout(delay) if delay.is_zero() => schedulers.immediate.schedule(callback, delay),
+        Wake::Timeout(delay) => schedulers.delayed.schedule(callback, delay),
+    })
+}
@@ -0,0 +1,55 @@
+//! The global timer on WASI targets.
+//!
+//! Unlike JS hosts, WASI has no event loop that calls back into the program,
+//! so the global `Timer` is stored here and driven by whoever is waiting for
+//! it, see `crate::tokio::wasi`.
+
+use futures::task::{self, ArcWake};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::task::Context;
+
+use crate::std::Instant;
+use crate::timer::{Timer, TimerHandle};
+
+static TIMER: OnceLock<Mutex<Timer>> = OnceLock::new();
+
+fn timer() -> &'static Mutex<Timer> {
+    TIMER.get_or_init(|| Mutex::new(Timer::new()))
+}
+
+/// Creates the global `Timer` and returns a handle to it.
+pub(crate) fn run() -> TimerHandle {
+    timer().lock().handle()
+}
+
+/// Processes the pending timer updates, fires every timer that is due and
+/// returns the instant at which the next one is due.
+pub(crate) fn turn() -> Option<Instant> {
+    let mut timer = timer().lock();
+
+    // New timers wake the `Timer` task, which only matters while the clock is
+    // paused. Otherwise the next call to `turn` picks them up.
+    let waker = task::waker(Arc::new(Waker));
+    let _ = Future::poll(Pin::new(&mut *timer), &mut Context::from_waker(&waker));
+
+    timer.advance_to(Instant::now());
+    timer.next_event()
+}
+
+struct Waker;
+
+impl ArcWake for Waker {
+    #[cfg(feature = "tokio-test-util")]
+    fn wake_by_ref(_arc_self: &Arc<Self>) {
+        // Like on JS hosts, a paused clock fires the timers right away.
+        if super::clock::clock().paused() {
+            turn();
+        }
+    }
+
+    #[cfg(not(feature = "tokio-test-util"))]
+    fn wake_by_ref(_arc_self: &Arc<Self>) {}
+}
@@ -1,5 +1,4 @@
 use futures::future::poll_fn;
-use pin_utils::unsafe_pinned;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::time::Duration;
@@ -7,6 +6,7 @@ use std::time::Duration;
 use futures::prelude::*;
 
 use crate::std::Instant;
+use crate::timer::TimerHandle;
 use crate::tokio::Sleep;
 
 /// A stream representing notifications at fixed interval
@@ -26,8 +26,6 @@ pub struct Interval {
 }
 
 impl Interval {
-    unsafe_pinned!(sleep: Sleep);
-
     /// Creates a new interval which will fire at `dur` time into the future,
     /// and will repeat every `dur` interval after. The first tick completes immediately.
     ///
@@ -43,8 +41,17 @@ impl Interval {
     /// The returned object will be bound to the default timer for this thread.
     /// The default timer will be spun up in a helper thread on first use.
     pub(crate) fn new_at(at: Instant, dur: Duration) -> Interval {
+        Interval::new_handle(at, dur, Default::default())
+    }
+
+    /// Creates a new interval which will fire at the time specified by `at`,
+    /// and then will repeat every `dur` interval after
+    ///
+    /// The returned object will be bound to the timer specified by the
+    /// `handle` argument.
+    pub fn new_handle(at: Instant, dur: Duration, handle: TimerHandle) -> Interval {
         Interval {
-            sleep: Sleep::new_at(at),
+            sleep: Sleep::new_handle(at, handle),
             interval: dur,
             missed_tick_behavior: MissedTickBehavior::default(),
         }
@@ -56,7 +63,7 @@ impl Interval {
     }
 
     pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
-        if Pin::new(&mut *self).sleep().poll(cx).is_pending() {
+        if Pin::new(&mut self.sleep).poll(cx).is_pending() {
             return Poll::Pending;
         }
 
@@ -67,7 +74,9 @@ impl Interval {
             self.missed_tick_behavior
                 .next_timeout(timeout, now, self.interval)
         } else {
-            timeout + self.interval
+            timeout
+                .checked_add(self.interval)
+                .unwrap_or_else(Instant::far_future)
         };
 
         Pin::new(&mut self.sleep).reset(next);
@@ -75,7 +84,10 @@ impl Interval {
     }
 
     pub fn reset(&mut self) {
-        Pin::new(&mut self.sleep).reset(Instant::now() + self.interval);
+        let next = Instant::now()
+            .checked_add(self.interval)
+            .unwrap_or_else(Instant::far_future);
+        Pin::new(&mut self.sleep).reset(next);
     }
 
     /// Returns the [`MissedTickBehavior`] strategy currently being used.
@@ -106,24 +118,28 @@ impl MissedTickBehavior {
     /// If a tick is missed, this method is called to determine when the next tick should happen.
     fn next_timeout(&self, timeout: Instant, now: Instant, period: Duration) -> Instant {
         match self {
-            Self::Burst => timeout + period,
-            Self::Delay => now + period,
+            Self::Burst => timeout
+                .checked_add(period)
+                .unwrap_or_else(Instant::far_future),
+            Self::Delay => now.checked_add(period).unwrap_or_else(Instant::far_future),
             Self::Skip => {
-                now + period
-                    - Duration::from_nanos(
-                        ((now - timeout).as_nanos() % period.as_nanos())
-                            .try_into()
-                            // This operation is practically guaranteed not to
-                            // fail, as in order for it to fail, `period` would
-                            // have to be longer than `now - timeout`, and both
-                            // would have to be longer than 584 years.
-                            //
-                            // If it did fail, there's not a good way to pass
-                            // the error along to the user, so we just panic.
-                            .expect(
-                                "too much time has elapsed since the interval was supposed to tick",
-                            ),
-                    )
+                let Some(next) = now.checked_add(period) else {
+                    return Instant::far_future();
+                };
+                next - Duration::from_nanos(
+                    ((now - timeout).as_nanos() % period.as_nanos())
+                        .try_into()
+                        // This operation is practically guaranteed not to
+                        // fail, as in order for it to fail, `period` would
+                        // have to be longer than `now - timeout`, and both
+                        // would have to be longer than 584 years.
+                        //
+                        // If it did fail, there's not a good way to pass
+                        // the error along to the user, so we just panic.
+                        .expect(
+                            "too much time has elapsed since the interval was supposed to tick",
+                        ),
+                )
             }
         }
     }
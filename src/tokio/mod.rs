@@ -1,3 +1,5 @@
+pub use crate::timer::{SetDefaultError, Timer, TimerHandle};
+
 mod sleep;
 pub use sleep::*;
 
@@ -13,3 +15,6 @@ mod test_utils;
 pub use test_utils::*;
 
 pub mod error;
+
+#[cfg(target_os = "wasi")]
+pub mod wasi;
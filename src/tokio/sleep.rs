@@ -14,16 +14,23 @@ use std::time::Duration;
 pub struct Sleep {
     state: Option<Arc<Node<ScheduledTimer>>>,
     deadline: Instant,
+    /// Whether the timer is the default one, which is never expected to go
+    /// away.
+    global: bool,
 }
 
 impl Sleep {
-    /// Creates a new future which will fire at `dur` time into the future.
+    /// Creates a new future which will fire at `dur` time into the future, or
+    /// at [`Instant::far_future`] if that cannot be represented.
     ///
     /// The returned object will be bound to the default timer for this thread.
     /// The default timer will be spun up in a helper thread on first use.
     #[inline]
     pub(crate) fn new(dur: Duration) -> Sleep {
-        Sleep::new_at(Instant::now() + dur)
+        let deadline = Instant::now()
+            .checked_add(dur)
+            .unwrap_or_else(Instant::far_future);
+        Sleep::new_at(deadline)
     }
 
     /// Creates a new future which will fire at the time specified by `at`.
@@ -37,15 +44,19 @@ impl Sleep {
 
     /// Creates a new future which will fire at the time specified by `at`.
     ///
-    /// The returned instance of `Delay` will be bound to the timer specified by
-    /// the `handle` argument.
-    pub(crate) fn new_handle(at: Instant, handle: TimerHandle) -> Sleep {
+    /// The returned instance of `Sleep` will be bound to the timer specified by
+    /// the `handle` argument. If that timer is dropped, the future never
+    /// completes. Polling a future bound to the default timer panics instead
+    /// if it has gone away.
+    pub fn new_handle(at: Instant, handle: TimerHandle) -> Sleep {
+        let global = handle.is_global();
         let inner = match handle.inner.upgrade() {
             Some(i) => i,
             None => {
                 return Sleep {
                     state: None,
                     deadline: at,
+                    global,
                 }
             }
         };
@@ -64,6 +75,7 @@ impl Sleep {
             return Sleep {
                 state: None,
                 deadline: at,
+                global,
             };
         }
 
@@ -71,6 +83,7 @@ impl Sleep {
         Sleep {
             state: Some(state),
             deadline: at,
+            global,
         }
     }
 
@@ -160,6 +173,8 @@ impl Future for Sleep {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let state = match self.state {
             Some(ref state) => state,
+            // A separately owned timer that has gone away never fires.
+            None if !self.global => return Poll::Pending,
             None => panic!("timer has gone away"),
         };
 
@@ -174,7 +189,7 @@ impl Future for Sleep {
         // invalidated the second bit is set.
         match state.state.load(Ordering::SeqCst) {
             n if n & 0b01 != 0 => Poll::Ready(()),
-            n if n & 0b10 != 0 => panic!("Timer has gone away"),
+            n if n & 0b10 != 0 && self.global => panic!("Timer has gone away"),
             _ => Poll::Pending,
         }
     }
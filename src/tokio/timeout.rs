@@ -1,24 +1,25 @@
-use std::{task::Poll, time::Duration};
+use std::{pin::Pin, task::Poll, time::Duration};
 
 use futures::Future;
-use pin_utils::unsafe_pinned;
+use pin_project_lite::pin_project;
 
 use crate::std::Instant;
+use crate::timer::TimerHandle;
 
 use super::{error::Elapsed, Sleep};
 
-pub struct Timeout<T> {
-    delay: Sleep,
-    future: T,
+pin_project! {
+    pub struct Timeout<T> {
+        delay: Sleep,
+        #[pin]
+        future: T,
+    }
 }
 
 impl<F> Timeout<F>
 where
     F: Future,
 {
-    unsafe_pinned!(future: F);
-    unsafe_pinned!(delay: Sleep);
-
     pub(crate) fn new(dur: Duration, fut: F) -> Timeout<F> {
         Timeout {
             delay: Sleep::new(dur),
@@ -33,6 +34,17 @@ where
         }
     }
 
+    /// Requires `fut` to complete before the time specified by `at`.
+    ///
+    /// The returned object will be bound to the timer specified by the
+    /// `handle` argument.
+    pub fn new_handle(at: Instant, fut: F, handle: TimerHandle) -> Timeout<F> {
+        Timeout {
+            delay: Sleep::new_handle(at, handle),
+            future: fut,
+        }
+    }
+
     pub fn get_ref(&self) -> &F {
         &self.future
     }
@@ -52,16 +64,15 @@ where
 {
     type Output = Result<T::Output, Elapsed>;
 
-    fn poll(
-        mut self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> Poll<Self::Output> {
-        match self.as_mut().future().poll(cx) {
+    fn poll(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        match this.future.poll(cx) {
             Poll::Pending => {}
             Poll::Ready(other) => return Poll::Ready(Ok(other)),
         }
 
-        if self.delay().poll(cx).is_ready() {
+        if Pin::new(this.delay).poll(cx).is_ready() {
             Poll::Ready(Err(Elapsed::new()))
         } else {
             Poll::Pending
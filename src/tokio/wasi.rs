@@ -0,0 +1,127 @@
+//! Driving the timers on WASI targets.
+//!
+//! WASI has no event loop that could call back into the program, so timers
+//! only fire while one of the functions in this module runs. [`block_on`] is
+//! enough for simple programs. Executors that wait on other WASI pollables
+//! can add the pollable returned by `subscribe`, on WASI 0.2 targets, to
+//! their list, and call [`process_timers`] once it is ready.
+
+use futures::task::{self, ArcWake};
+use std::future::Future;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::std::Instant;
+use crate::timer::wasi::turn;
+
+/// Fires every timer that is due and returns the instant at which the next
+/// one is due, if any.
+///
+/// This never blocks.
+pub fn process_timers() -> Option<Instant> {
+    turn()
+}
+
+/// Blocks until the next timer is due, then fires it.
+///
+/// Returns right away if no timer is scheduled. With the `tokio-test-util`
+/// feature, a paused clock is advanced to the next timer instead.
+pub fn wait() {
+    if let Some(next) = process_timers() {
+        block_until(next);
+        process_timers();
+    }
+}
+
+/// Returns a pollable that becomes ready when the next timer is due, or
+/// `None` if no timer is scheduled.
+///
+/// Call [`process_timers`] once the pollable is ready to fire the timer, and
+/// subscribe again afterwards as timers created in the meantime are not
+/// covered by the returned pollable.
+#[cfg(target_env = "p2")]
+pub fn subscribe() -> Option<::wasi::io::poll::Pollable> {
+    let next = process_timers()?;
+    Some(::wasi::clocks::monotonic_clock::subscribe_duration(
+        nanos_until(next),
+    ))
+}
+
+/// Runs a future to completion on the current thread, firing timers while it
+/// is pending.
+///
+/// # Panics
+///
+/// Panics if the future is pending while no timer is scheduled and nothing
+/// woke it, as it would never complete.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let woken = Arc::new(Woken(AtomicBool::new(true)));
+    let waker = task::waker(woken.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if woken.0.swap(false, SeqCst) {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+
+        let next = process_timers();
+        if woken.0.load(SeqCst) {
+            continue;
+        }
+
+        match next {
+            Some(next) => block_until(next),
+            None => panic!("`block_on` future is pending but no timer is scheduled"),
+        }
+    }
+}
+
+struct Woken(AtomicBool);
+
+impl ArcWake for Woken {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.store(true, SeqCst);
+    }
+}
+
+fn block_until(deadline: Instant) {
+    // Nothing else can advance a paused clock while we block, so jump to the
+    // deadline like tokio's auto-advance.
+    #[cfg(feature = "tokio-test-util")]
+    {
+        let clock = crate::timer::clock::clock();
+        if clock.paused() {
+            clock.advance(duration_until(deadline));
+            return;
+        }
+    }
+
+    // Some runtimes round the timeout down, so this may wake up too early.
+    while Instant::now() < deadline {
+        #[cfg(target_env = "p2")]
+        ::wasi::clocks::monotonic_clock::subscribe_duration(nanos_until(deadline)).block();
+
+        #[cfg(not(target_env = "p2"))]
+        std::thread::sleep(duration_until(deadline));
+    }
+}
+
+#[cfg(target_env = "p2")]
+fn nanos_until(deadline: Instant) -> u64 {
+    u64::try_from(duration_until(deadline).as_nanos()).unwrap_or(u64::MAX)
+}
+
+fn duration_until(deadline: Instant) -> Duration {
+    let now = Instant::now();
+    if deadline > now {
+        deadline - now
+    } else {
+        Duration::new(0, 0)
+    }
+}
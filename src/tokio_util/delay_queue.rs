@@ -7,13 +7,13 @@
 use super::wheel::{self, Wheel};
 
 use crate::std::Instant;
+use crate::timer::TimerHandle;
 use crate::tokio::{sleep_until, Sleep};
 use futures::ready;
 use std::time::Duration;
 
 use core::ops::{Index, IndexMut};
 use slab::Slab;
-use std::cmp;
 use std::collections::HashMap;
 use std::convert::From;
 use std::fmt;
@@ -28,10 +28,15 @@ pub struct DelayQueue<T> {
     slab: SlabStorage<T>,
     wheel: Wheel<Stack<T>>,
     expired: Stack<T>,
+    /// Entries further out than the wheel can hold, which are moved to the
+    /// wheel once it has advanced far enough.
+    far_future: Stack<T>,
     delay: Option<Pin<Box<Sleep>>>,
     wheel_now: u64,
     start: Instant,
     waker: Option<Waker>,
+    /// The timer of the delays, or `None` for the global timer.
+    handle: Option<TimerHandle>,
 }
 
 #[derive(Default)]
@@ -233,6 +238,7 @@ struct Data<T> {
     inner: T,
     when: u64,
     expired: bool,
+    far_future: bool,
     next: Option<Key>,
     prev: Option<Key>,
 }
@@ -249,10 +255,21 @@ impl<T> DelayQueue<T> {
             wheel: Wheel::new(),
             slab: SlabStorage::with_capacity(capacity),
             expired: Stack::default(),
+            far_future: Stack::default(),
             delay: None,
             wheel_now: 0,
             start: Instant::now(),
             waker: None,
+            handle: None,
+        }
+    }
+
+    /// Creates a new, empty, `DelayQueue` whose delays are bound to the timer
+    /// specified by the `handle` argument.
+    pub fn new_handle(handle: TimerHandle) -> DelayQueue<T> {
+        DelayQueue {
+            handle: Some(handle),
+            ..DelayQueue::new()
         }
     }
 
@@ -268,6 +285,7 @@ impl<T> DelayQueue<T> {
             inner: value,
             when,
             expired: false,
+            far_future: false,
             next: None,
             prev: None,
         });
@@ -291,7 +309,7 @@ impl<T> DelayQueue<T> {
             if let Some(ref mut delay) = &mut self.delay {
                 delay.as_mut().reset(delay_time);
             } else {
-                self.delay = Some(Box::pin(sleep_until(delay_time)));
+                self.delay = Some(Box::pin(self.sleep_until(delay_time)));
             }
         }
 
@@ -324,7 +342,10 @@ impl<T> DelayQueue<T> {
 
     #[track_caller]
     pub fn insert(&mut self, value: T, timeout: Duration) -> Key {
-        self.insert_at(value, Instant::now() + timeout)
+        let when = Instant::now()
+            .checked_add(timeout)
+            .unwrap_or_else(Instant::far_future);
+        self.insert_at(value, when)
     }
 
     #[track_caller]
@@ -339,7 +360,10 @@ impl<T> DelayQueue<T> {
                 // The delay is already expired, store it in the expired queue
                 self.expired.push(key, &mut self.slab);
             }
-            Err((_, err)) => panic!("invalid deadline; err={err:?}"),
+            Err((_, InsertError::Invalid)) => {
+                self.slab[key].far_future = true;
+                self.far_future.push(key, &mut self.slab);
+            }
         }
     }
 
@@ -350,6 +374,8 @@ impl<T> DelayQueue<T> {
         // Special case the `expired` queue
         if self.slab[*key].expired {
             self.expired.remove(key, &mut self.slab);
+        } else if self.slab[*key].far_future {
+            self.far_future.remove(key, &mut self.slab);
         } else {
             self.wheel.remove(key, &mut self.slab);
         }
@@ -367,7 +393,7 @@ impl<T> DelayQueue<T> {
             match (next_deadline, &mut self.delay) {
                 (None, _) => self.delay = None,
                 (Some(deadline), Some(delay)) => delay.as_mut().reset(deadline),
-                (Some(deadline), None) => self.delay = Some(Box::pin(sleep_until(deadline))),
+                (Some(deadline), None) => self.delay = Some(Box::pin(self.sleep_until(deadline))),
             }
         }
 
@@ -395,6 +421,7 @@ impl<T> DelayQueue<T> {
 
         self.slab[*key].when = when;
         self.slab[*key].expired = false;
+        self.slab[*key].far_future = false;
 
         self.insert_idx(when, *key);
 
@@ -414,19 +441,45 @@ impl<T> DelayQueue<T> {
     }
 
     fn next_deadline(&mut self) -> Option<Instant> {
-        self.wheel
-            .poll_at()
-            .map(|poll_at| self.start + Duration::from_millis(poll_at))
+        let mut next = self.far_future.head;
+        let mut far_future = None::<u64>;
+        while let Some(key) = next {
+            let when = self.slab[key].when;
+            far_future = Some(far_future.map_or(when, |far_future| far_future.min(when)));
+            next = self.slab[key].next;
+        }
+
+        let poll_at = match (self.wheel.poll_at(), far_future) {
+            (Some(poll_at), Some(far_future)) => Some(poll_at.min(far_future)),
+            (poll_at, far_future) => poll_at.or(far_future),
+        };
+        poll_at.map(|poll_at| self.start + Duration::from_millis(poll_at))
+    }
+
+    /// Moves the far-future entries that the wheel can hold by now to it, or
+    /// to the expired queue if they are due.
+    fn promote_far_future(&mut self) {
+        use self::wheel::Stack;
+
+        let mut far_future = std::mem::take(&mut self.far_future);
+        while let Some(key) = far_future.pop(&mut self.slab) {
+            self.slab[key].far_future = false;
+            self.insert_idx(self.slab[key].when, key);
+        }
     }
 
     #[track_caller]
     pub fn reset(&mut self, key: &Key, timeout: Duration) {
-        self.reset_at(key, Instant::now() + timeout);
+        let when = Instant::now()
+            .checked_add(timeout)
+            .unwrap_or_else(Instant::far_future);
+        self.reset_at(key, when);
     }
 
     pub fn clear(&mut self) {
         self.slab.clear();
         self.expired = Stack::default();
+        self.far_future = Stack::default();
         self.wheel = Wheel::new();
         self.delay = None;
     }
@@ -486,9 +539,16 @@ impl<T> DelayQueue<T> {
             // We poll the wheel to get the next value out before finding the next deadline.
             let wheel_idx = self.wheel.poll(self.wheel_now, &mut self.slab);
 
-            self.delay = self.next_deadline().map(|when| Box::pin(sleep_until(when)));
+            // The wheel advanced, so it may hold far-future entries now.
+            if !self.far_future.is_empty() {
+                self.promote_far_future();
+            }
 
-            if let Some(idx) = wheel_idx {
+            self.delay = self
+                .next_deadline()
+                .map(|when| Box::pin(self.sleep_until(when)));
+
+            if let Some(idx) = wheel_idx.or_else(|| self.expired.pop(&mut self.slab)) {
                 return Poll::Ready(Some(idx));
             }
 
@@ -498,6 +558,15 @@ impl<T> DelayQueue<T> {
         }
     }
 
+    fn sleep_until(&self, when: Instant) -> Sleep {
+        match &self.handle {
+            Some(handle) => Sleep::new_handle(when, handle.clone()),
+            None => sleep_until(when),
+        }
+    }
+
+    /// Converts a deadline to milliseconds since `start`. Deadlines in the
+    /// past expire right away.
     fn normalize_deadline(&self, when: Instant) -> u64 {
         let when = if when < self.start {
             0
@@ -505,7 +574,7 @@ impl<T> DelayQueue<T> {
             super::ms(when - self.start, super::Round::Up)
         };
 
-        cmp::max(when, self.wheel.elapsed())
+        when.max(self.wheel.elapsed())
     }
 }
 
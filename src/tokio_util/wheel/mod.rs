@@ -16,6 +16,7 @@ pub(crate) struct Wheel<T> {
 
 const NUM_LEVELS: usize = 6;
 
+/// The furthest into the future, in milliseconds, that an entry can expire.
 const MAX_DURATION: u64 = (1 << (6 * NUM_LEVELS)) - 1;
 
 #[derive(Debug)]
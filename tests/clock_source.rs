@@ -0,0 +1,39 @@
+// Installing a clock source affects the whole program, so this test lives in
+// its own binary, where nothing has read the clock before.
+#![cfg(any(target_family = "wasm", not(feature = "native-fallback")))]
+
+use std::time::Duration;
+
+use wasmtimer::std::{set_clock_source, ClockSource, Instant, SystemTime, UNIX_EPOCH};
+
+struct FixedClock;
+
+impl ClockSource for FixedClock {
+    fn monotonic_now(&self) -> Duration {
+        Duration::from_millis(10)
+    }
+
+    fn time_origin(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_000)
+    }
+
+    fn system_now(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(1_000_010)
+    }
+}
+
+#[cfg_attr(
+    all(target_family = "wasm", not(target_os = "wasi")),
+    wasm_bindgen_test::wasm_bindgen_test
+)]
+#[cfg_attr(not(all(target_family = "wasm", not(target_os = "wasi"))), test)]
+fn test_custom_clock_source() {
+    assert!(set_clock_source(FixedClock).is_ok());
+    assert!(set_clock_source(FixedClock).is_err());
+    assert_eq!(
+        SystemTime::now(),
+        UNIX_EPOCH + Duration::from_millis(1_000_010)
+    );
+    assert_eq!(Instant::now().to_system_time(), SystemTime::now());
+    assert_eq!(Instant::now(), Instant::now());
+}
@@ -0,0 +1,54 @@
+// Tests of the global timer driver against the real clock. They live in their
+// own binary as `tests/web.rs` pauses the clock.
+#![cfg(all(target_family = "wasm", not(target_os = "wasi"), feature = "tokio"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use futures::future::join_all;
+use wasm_bindgen_test::wasm_bindgen_test;
+use wasmtimer::std::Instant;
+use wasmtimer::tokio::sleep;
+
+#[cfg(browser)]
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+pub async fn test_zero_sleeps_are_not_clamped() {
+    let start = Instant::now();
+    for _ in 0..100 {
+        sleep(Duration::ZERO).await;
+    }
+    // `setTimeout(0)` would take at least 4ms each once nested.
+    assert!(start.elapsed() < Duration::from_millis(300));
+}
+
+#[wasm_bindgen_test]
+pub async fn test_reset_to_earlier_deadline() {
+    let start = Instant::now();
+    let mut sleep = Box::pin(sleep(Duration::from_secs(60)));
+    // Let the driver arm its timeout for the first deadline.
+    wasmtimer::tokio::sleep(Duration::from_millis(1)).await;
+    sleep
+        .as_mut()
+        .reset(Instant::now() + Duration::from_millis(20));
+    sleep.await;
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(20));
+    assert!(elapsed < Duration::from_secs(5));
+}
+
+#[wasm_bindgen_test]
+pub async fn test_sleeps_complete_in_order() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let sleeps = [30u64, 10, 50, 0, 20].map(|millis| {
+        let order = order.clone();
+        async move {
+            sleep(Duration::from_millis(millis)).await;
+            order.borrow_mut().push(millis);
+        }
+    });
+    join_all(sleeps).await;
+    assert_eq!(*order.borrow(), [0, 10, 20, 30, 50]);
+}
@@ -0,0 +1,162 @@
+#![cfg(target_os = "wasi")]
+
+use std::time::Duration;
+
+use wasmtimer::std::{Instant, SystemTime, UNIX_EPOCH};
+
+#[test]
+fn test_system_time_now() {
+    let now = SystemTime::now();
+    // 2020-01-01
+    assert!(now > UNIX_EPOCH + Duration::from_secs(1_577_836_800));
+    assert!(SystemTime::now() >= now);
+}
+
+#[test]
+fn test_instant_now() {
+    let start = Instant::now();
+    let mut last = start;
+    for _ in 0..1000 {
+        let now = Instant::now();
+        assert!(now >= last);
+        last = now;
+    }
+    assert!(last.duration_since(start) < Duration::from_secs(60));
+}
+
+#[test]
+fn test_instant_system_time_conversion() {
+    let instant = Instant::now();
+    let system_time = SystemTime::now();
+    let diff = match instant.to_system_time().duration_since(system_time) {
+        Ok(diff) => diff,
+        Err(err) => err.duration(),
+    };
+    assert!(diff < Duration::from_secs(1));
+}
+
+#[test]
+fn test_thread_sleep() {
+    // `std::time::Instant`, as the clock may be paused by the tokio tests.
+    let start = std::time::Instant::now();
+    wasmtimer::std::thread::sleep(Duration::from_millis(20));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    wasmtimer::std::thread::try_sleep(Duration::from_millis(20)).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+#[cfg(feature = "tokio")]
+pub mod tokio_tests {
+    use std::time::Duration;
+
+    use wasmtimer::std::Instant;
+    use wasmtimer::tokio::sleep;
+    use wasmtimer::tokio::wasi::block_on;
+
+    #[cfg(not(feature = "tokio-test-util"))]
+    #[test]
+    fn test_sleep() {
+        use wasmtimer::tokio::wasi::process_timers;
+
+        let start = Instant::now();
+        block_on(sleep(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(process_timers(), None);
+    }
+
+    #[cfg(not(feature = "tokio-test-util"))]
+    #[test]
+    fn test_interval() {
+        use wasmtimer::tokio::interval;
+
+        let start = Instant::now();
+        block_on(async {
+            let mut interval = interval(Duration::from_millis(10));
+            for _ in 0..3 {
+                interval.tick().await;
+            }
+        });
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[cfg(not(feature = "tokio-test-util"))]
+    #[test]
+    fn test_timeout() {
+        use wasmtimer::tokio::timeout;
+
+        let result = block_on(timeout(
+            Duration::from_millis(10),
+            sleep(Duration::from_secs(10)),
+        ));
+        assert!(result.is_err());
+
+        let result = block_on(timeout(
+            Duration::from_secs(10),
+            sleep(Duration::from_millis(10)),
+        ));
+        assert!(result.is_ok());
+    }
+
+    #[cfg(not(feature = "tokio-test-util"))]
+    #[test]
+    fn test_wait() {
+        use wasmtimer::tokio::wasi::wait;
+
+        let mut sleep = Box::pin(sleep(Duration::from_millis(10)));
+        assert!(!sleep.is_elapsed());
+        block_on(async {
+            assert!(futures::poll!(sleep.as_mut()).is_pending());
+        });
+        wait();
+        assert!(sleep.is_elapsed());
+    }
+
+    #[cfg(all(feature = "tokio-util", not(feature = "tokio-test-util")))]
+    #[test]
+    fn test_delay_queue() {
+        use futures::StreamExt;
+        use wasmtimer::tokio_util::DelayQueue;
+
+        let expired = block_on(async {
+            let mut queue = DelayQueue::new();
+            queue.insert("b", Duration::from_millis(20));
+            queue.insert("a", Duration::from_millis(10));
+            let mut expired = vec![];
+            while let Some(entry) = queue.next().await {
+                expired.push(entry.into_inner());
+            }
+            expired
+        });
+        assert_eq!(expired, ["a", "b"]);
+    }
+
+    #[cfg(feature = "tokio-test-util")]
+    fn pause() {
+        static PAUSE: std::sync::Once = std::sync::Once::new();
+        PAUSE.call_once(wasmtimer::tokio::pause);
+    }
+
+    #[cfg(feature = "tokio-test-util")]
+    #[test]
+    fn test_advance() {
+        use wasmtimer::tokio::advance;
+
+        pause();
+        let start = Instant::now();
+        block_on(async {
+            let sleep = sleep(Duration::from_secs(60));
+            advance(Duration::from_secs(60)).await;
+            sleep.await;
+        });
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+    }
+
+    #[cfg(feature = "tokio-test-util")]
+    #[test]
+    fn test_auto_advance() {
+        pause();
+        let start = Instant::now();
+        block_on(sleep(Duration::from_secs(60)));
+        assert_eq!(start.elapsed(), Duration::from_secs(60));
+    }
+}
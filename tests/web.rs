@@ -1,19 +1,562 @@
-use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+// WASI targets have no JS host, see `tests/wasi.rs` instead.
+#![cfg(not(target_os = "wasi"))]
+
+#[cfg(any(
+    target_family = "wasm",
+    not(feature = "native-fallback"),
+    feature = "tokio-test-util"
+))]
+use wasm_bindgen_test::wasm_bindgen_test;
 
 #[cfg(browser)]
-wasm_bindgen_test_configure!(run_in_browser);
-
-#[cfg(feature = "serde")]
-#[wasm_bindgen_test]
-pub fn test_serde() {
-    use wasmtimer::std::SystemTime;
-    let now = SystemTime::now();
-    let serialized = serde_json::to_string(&now).unwrap();
-    let deserialized: SystemTime = serde_json::from_str(&serialized).unwrap();
-    assert_eq!(now, deserialized);
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+// These cover APIs that only exist on the WASM implementation.
+#[cfg(any(target_family = "wasm", not(feature = "native-fallback")))]
+pub mod std_tests {
+    use super::*;
+
+    #[wasm_bindgen_test]
+    pub fn test_system_time_now() {
+        use std::time::Duration;
+        use wasmtimer::std::{SystemTime, UNIX_EPOCH};
+        let before = js_sys::Date::now();
+        let now = SystemTime::now();
+        let after = js_sys::Date::now();
+        let since_epoch = now.duration_since(UNIX_EPOCH).unwrap();
+        assert!(since_epoch >= Duration::from_millis(before as u64));
+        assert!(since_epoch <= Duration::from_millis(after as u64));
+        assert_eq!(since_epoch.subsec_nanos() % 1_000_000, 0);
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_instant_system_time_conversion() {
+        use std::time::Duration;
+        use wasmtimer::std::{Instant, SystemTime, UNIX_EPOCH};
+        let now = SystemTime::now();
+        let instant = now.to_instant();
+        assert_eq!(instant.to_system_time(), now);
+        assert_eq!(SystemTime::from(Instant::from(now)), now);
+        assert_eq!(
+            (now + Duration::from_millis(1500)).to_instant(),
+            instant + Duration::from_millis(1500)
+        );
+
+        // Times before the context started saturate to the time origin.
+        assert!(UNIX_EPOCH.to_instant() <= instant);
+    }
+
+    // The mocked clock of `tokio-test-util` is detached from the wall clock.
+    #[cfg(not(feature = "tokio-test-util"))]
+    #[wasm_bindgen_test]
+    pub fn test_instant_system_time_drift() {
+        use std::time::Duration;
+        use wasmtimer::std::{Instant, SystemTime};
+        let from_instant = Instant::now().to_system_time();
+        let now = SystemTime::now();
+        let drift = match now.duration_since(from_instant) {
+            Ok(drift) => drift,
+            Err(err) => err.duration(),
+        };
+        assert!(drift < Duration::from_secs(1), "drift was {:?}", drift);
+    }
+
+    #[wasm_bindgen::prelude::wasm_bindgen(inline_js = "
+        export function identity(value) { return value; }
+        export function type_of(value) { return typeof value; }
+    ")]
+    extern "C" {
+        #[wasm_bindgen(js_name = identity)]
+        fn system_time_identity(time: wasmtimer::std::SystemTime) -> wasmtimer::std::SystemTime;
+        #[wasm_bindgen(js_name = identity)]
+        fn instant_identity(instant: wasmtimer::std::Instant) -> wasmtimer::std::Instant;
+        #[wasm_bindgen(js_name = type_of)]
+        fn system_time_type_of(time: wasmtimer::std::SystemTime) -> String;
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_js_date_conversion() {
+        use js_sys::Date;
+        use std::time::Duration;
+        use wasm_bindgen::JsValue;
+        use wasmtimer::std::{SystemTime, UNIX_EPOCH};
+
+        let date = Date::new(&JsValue::from_str("1969-07-20T20:17:40.500Z"));
+        let time = SystemTime::try_from(&date).unwrap();
+        assert_eq!(
+            UNIX_EPOCH.duration_since(time).unwrap(),
+            Duration::from_millis(14_182_939_500)
+        );
+        assert_eq!(Date::from(time).get_time(), date.get_time());
+
+        // Sub-millisecond precision is truncated.
+        let date = Date::from(UNIX_EPOCH + Duration::from_micros(1_999));
+        assert_eq!(date.get_time(), 1.0);
+
+        let invalid = Date::new(&JsValue::from_f64(f64::NAN));
+        assert!(SystemTime::try_from(invalid).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_calendar_parts() {
+        use js_sys::Date;
+        use std::time::Duration;
+        use wasmtimer::std::{SystemTime, UNIX_EPOCH};
+
+        let times = [
+            UNIX_EPOCH - Duration::from_millis(14_182_939_500),
+            UNIX_EPOCH + Duration::from_millis(951_825_599_999),
+            // Around the daylight saving time changes in Europe and the US.
+            UNIX_EPOCH + Duration::from_secs(1_679_794_200),
+            UNIX_EPOCH + Duration::from_secs(1_699_162_200),
+            UNIX_EPOCH - Duration::from_secs(62_198_755_200),
+            SystemTime::now(),
+        ];
+        for time in times {
+            let date = Date::from(time);
+
+            // `js_sys` returns years as `u32`, wrapping negative years.
+            let utc = time.to_utc_parts();
+            assert_eq!(utc.year as u32, date.get_utc_full_year());
+            assert_eq!(utc.month, date.get_utc_month() + 1);
+            assert_eq!(utc.day, date.get_utc_date());
+            assert_eq!(utc.hour, date.get_utc_hours());
+            assert_eq!(utc.minute, date.get_utc_minutes());
+            assert_eq!(utc.second, date.get_utc_seconds());
+            assert_eq!(utc.nanosecond / 1_000_000, date.get_utc_milliseconds());
+            assert_eq!(utc.weekday, date.get_utc_day());
+            assert_eq!(utc.offset, 0);
+
+            let local = time.to_local_parts().unwrap();
+            assert_eq!(
+                local.offset,
+                (-date.get_timezone_offset() * 60.0).round() as i32
+            );
+            assert_eq!(local.year as u32, date.get_full_year());
+            assert_eq!(local.month, date.get_month() + 1);
+            assert_eq!(local.day, date.get_date());
+            assert_eq!(local.hour, date.get_hours());
+            assert_eq!(local.minute, date.get_minutes());
+            assert_eq!(local.second, date.get_seconds());
+            assert_eq!(local.weekday, date.get_day());
+        }
+
+        let far_future = UNIX_EPOCH + Duration::from_secs(10_000_000_000_000);
+        assert_eq!(far_future.to_utc_parts().year, 318_857);
+        assert!(far_future.local_offset().is_err());
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_js_value_conversion() {
+        use js_sys::Date;
+        use std::time::Duration;
+        use wasm_bindgen::JsValue;
+        use wasmtimer::std::{Instant, SystemTime, UNIX_EPOCH};
+
+        let time = SystemTime::try_from(JsValue::from_f64(1500.0)).unwrap();
+        assert_eq!(time, UNIX_EPOCH + Duration::from_millis(1500));
+        let date = Date::new(&JsValue::from_f64(1500.0));
+        assert_eq!(SystemTime::try_from(JsValue::from(date)).unwrap(), time);
+        assert_eq!(JsValue::from(time).as_f64(), Some(1500.0));
+        assert!(SystemTime::try_from(JsValue::from_str("1500")).is_err());
+        assert!(SystemTime::try_from(JsValue::from_f64(f64::NAN)).is_err());
+
+        let instant = Instant::try_from(JsValue::from_f64(12.5)).unwrap();
+        assert_eq!(JsValue::from(instant).as_f64(), Some(12.5));
+        assert!(Instant::try_from(JsValue::from_f64(-1.0)).is_err());
+        assert!(Instant::try_from(JsValue::NULL).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_wasm_abi() {
+        use std::time::Duration;
+        use wasmtimer::std::{Instant, SystemTime, UNIX_EPOCH};
+
+        let time = UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_250);
+        assert_eq!(system_time_identity(time), time);
+        assert_eq!(system_time_type_of(time), "number");
+
+        let now = SystemTime::now();
+        assert_eq!(system_time_identity(now), now);
+
+        let instant = Instant::now();
+        assert_eq!(instant_identity(instant), instant);
+    }
+
+    #[wasm_bindgen::prelude::wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = ["globalThis", "performance"], js_name = now)]
+        fn performance_now() -> f64;
+    }
+
+    // The mocked clock of `tokio-test-util` is detached from `performance.now()`.
+    #[cfg(not(feature = "tokio-test-util"))]
+    #[wasm_bindgen_test]
+    pub fn test_high_res_timestamp() {
+        use wasmtimer::std::Instant;
+        let timestamp = performance_now();
+        let instant = Instant::from_high_res_timestamp(timestamp);
+        let now = Instant::now();
+        assert!(instant <= now);
+        assert!(now.as_high_res_timestamp() >= timestamp);
+        assert!((instant.as_high_res_timestamp() - timestamp).abs() <= 0.0005);
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_thread_sleep() {
+        use wasmtimer::std::thread::{sleep, try_sleep};
+
+        // `performance.now`, as the clock may be paused by `tokio-test-util`.
+        let start = performance_now();
+        sleep(std::time::Duration::from_millis(20));
+        assert!(performance_now() - start >= 20.0);
+
+        // Node.js allows `Atomics.wait` on the main thread, browsers do not.
+        let start = performance_now();
+        let result = try_sleep(std::time::Duration::from_millis(20));
+        if cfg!(browser) {
+            assert!(result.is_err());
+        } else {
+            result.unwrap();
+            assert!(performance_now() - start >= 20.0);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_instant_monotonic() {
+        use wasmtimer::std::Instant;
+        let mut last = Instant::now();
+        for _ in 0..1000 {
+            let now = Instant::now();
+            assert!(now >= last);
+            last = now;
+        }
+        assert_eq!(Instant::monotonic_clamp_count(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[wasm_bindgen_test]
+    pub fn test_serde() {
+        use wasmtimer::std::SystemTime;
+        let now = SystemTime::now();
+        let serialized = serde_json::to_string(&now).unwrap();
+        let deserialized: SystemTime = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(now, deserialized);
+    }
+
+    #[cfg(feature = "serde")]
+    #[wasm_bindgen_test]
+    pub fn test_instant_serde() {
+        use wasmtimer::std::{Instant, SystemTime, UNIX_EPOCH};
+        let now = Instant::now();
+        let serialized = serde_json::to_string(&now).unwrap();
+        let deserialized: Instant = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(now, deserialized);
+
+        // The encoding is the wall-clock time of the instant.
+        let wall_clock: SystemTime = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(wall_clock, now.to_system_time());
+
+        // Instants from before this context started saturate to its time origin.
+        let deserialized: Instant =
+            serde_json::from_str(&serde_json::to_string(&UNIX_EPOCH).unwrap()).unwrap();
+        assert!(deserialized <= now);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[wasm_bindgen_test]
+    pub fn test_chrono_now_utc() {
+        use std::time::Duration;
+        use wasmtimer::std::SystemTime;
+
+        let now = wasmtimer::std::chrono::now_utc();
+        let diff = SystemTime::now()
+            .duration_since(SystemTime::from(now))
+            .unwrap();
+        assert!(diff < Duration::from_secs(1));
+    }
+
+    #[cfg(feature = "intl")]
+    #[wasm_bindgen_test]
+    pub fn test_intl_format_locale() {
+        use std::time::Duration;
+        use wasmtimer::std::intl::{DateTimeFormatOptions, DateTimeStyle};
+        use wasmtimer::std::UNIX_EPOCH;
+
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let options = DateTimeFormatOptions {
+            date_style: Some(DateTimeStyle::Long),
+            time_zone: Some("UTC".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            time.format_locale(Some("en-US"), &options).unwrap(),
+            "November 14, 2023"
+        );
+        assert_eq!(
+            time.format_locale(Some("de-DE"), &options).unwrap(),
+            "14. November 2023"
+        );
+        // Served from the cache.
+        assert_eq!(
+            time.format_locale(Some("en-US"), &options).unwrap(),
+            "November 14, 2023"
+        );
+
+        let options = DateTimeFormatOptions {
+            time_style: Some(DateTimeStyle::Short),
+            time_zone: Some("Asia/Kolkata".to_string()),
+            hour12: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            time.format_locale(Some("en-US"), &options).unwrap(),
+            "03:43"
+        );
+
+        let invalid = DateTimeFormatOptions {
+            time_zone: Some("Not/A_Zone".to_string()),
+            ..Default::default()
+        };
+        assert!(time.format_locale(Some("en-US"), &invalid).is_err());
+        assert!(time
+            .format_locale(Some("not a locale"), &Default::default())
+            .is_err());
+        let far_future = UNIX_EPOCH + Duration::from_secs(10_000_000_000_000);
+        assert!(far_future
+            .format_locale(Some("en-US"), &Default::default())
+            .is_err());
+    }
+
+    #[cfg(feature = "intl")]
+    #[wasm_bindgen_test]
+    pub fn test_intl_format_relative() {
+        use std::time::Duration;
+        use wasmtimer::std::intl::{
+            format_relative, RelativeNumeric, RelativeStyle, RelativeTimeFormatOptions,
+        };
+        use wasmtimer::std::SystemTime;
+
+        let options = RelativeTimeFormatOptions::default();
+        assert_eq!(
+            format_relative(Duration::from_secs(200), Some("en"), options).unwrap(),
+            "3 minutes ago"
+        );
+        assert_eq!(
+            format_relative(Duration::from_secs(86_400), Some("en"), options).unwrap(),
+            "1 day ago"
+        );
+        let auto = RelativeTimeFormatOptions {
+            numeric: RelativeNumeric::Auto,
+            style: RelativeStyle::Long,
+        };
+        assert_eq!(
+            format_relative(Duration::from_secs(86_400), Some("en"), auto).unwrap(),
+            "yesterday"
+        );
+        assert_eq!(
+            format_relative(Duration::from_secs(7_200), Some("de"), options).unwrap(),
+            "vor 2 Stunden"
+        );
+
+        let later = SystemTime::now() + Duration::from_secs(2 * 3_600 + 60);
+        assert_eq!(
+            later.format_relative(Some("en"), options).unwrap(),
+            "in 2 hours"
+        );
+        let earlier = SystemTime::now() - Duration::from_secs(3 * 60 + 10);
+        assert_eq!(
+            earlier.format_relative(Some("en"), options).unwrap(),
+            "3 minutes ago"
+        );
+
+        assert!(format_relative(Duration::ZERO, Some("not a locale"), options).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[wasm_bindgen_test]
+    pub fn test_time_now_utc() {
+        use std::time::Duration;
+        use wasmtimer::std::SystemTime;
+
+        let now = wasmtimer::std::time::now_utc();
+        let diff = SystemTime::now()
+            .duration_since(SystemTime::from(now))
+            .unwrap();
+        assert!(diff < Duration::from_secs(1));
+    }
 }
 
-#[cfg(feature = "tokio-test-util")]
+#[cfg(all(
+    feature = "profile",
+    any(target_family = "wasm", not(feature = "native-fallback"))
+))]
+pub mod profile_tests {
+    use super::*;
+    use wasm_bindgen::prelude::wasm_bindgen;
+    use wasmtimer::profile::{ProfileFutureExt, Span};
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = ["globalThis", "performance"], js_name = getEntriesByName)]
+        fn get_entries_by_name(name: &str, entry_type: &str) -> js_sys::Array;
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_span() {
+        let outer = Span::new("test_span outer");
+        {
+            let _inner = Span::new("test_span inner");
+            assert_eq!(
+                get_entries_by_name("test_span inner", "measure").length(),
+                0
+            );
+        }
+        assert_eq!(
+            get_entries_by_name("test_span inner", "measure").length(),
+            1
+        );
+        assert_eq!(
+            get_entries_by_name("test_span outer", "measure").length(),
+            0
+        );
+        drop(outer);
+        assert_eq!(
+            get_entries_by_name("test_span outer", "measure").length(),
+            1
+        );
+        // The start marks are removed once measured.
+        assert_eq!(
+            get_entries_by_name("test_span outer #0", "mark").length(),
+            0
+        );
+    }
+
+    #[wasm_bindgen_test]
+    pub async fn test_profiled() {
+        let future = std::future::ready(42).profiled("test_profiled");
+        assert_eq!(get_entries_by_name("test_profiled", "measure").length(), 0);
+        assert_eq!(future.await, 42);
+        assert_eq!(get_entries_by_name("test_profiled", "measure").length(), 1);
+    }
+}
+
+// Timers bound to a separate `Timer`, which only fire when it is advanced.
+#[cfg(all(
+    feature = "tokio",
+    any(target_family = "wasm", not(feature = "native-fallback"))
+))]
+pub mod timer_tests {
+    use super::*;
+    use std::future::{pending, Future};
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+    use std::time::Duration;
+    use wasmtimer::std::Instant;
+    use wasmtimer::tokio::{Interval, Sleep, Timeout, Timer};
+
+    #[wasm_bindgen_test]
+    pub fn test_sleep() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut timer = Timer::new();
+        let start = Instant::now();
+
+        let mut sleep = pin!(Sleep::new_handle(
+            start + Duration::from_secs(10),
+            timer.handle()
+        ));
+        assert_eq!(sleep.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(timer.next_event(), Some(start + Duration::from_secs(10)));
+        timer.advance_to(start + Duration::from_secs(5));
+        assert_eq!(sleep.as_mut().poll(&mut cx), Poll::Pending);
+        timer.advance_to(start + Duration::from_secs(10));
+        assert_eq!(sleep.as_mut().poll(&mut cx), Poll::Ready(()));
+        assert_eq!(timer.next_event(), None);
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_interval() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut timer = Timer::new();
+        let start = Instant::now();
+
+        let mut interval = Interval::new_handle(start, Duration::from_secs(10), timer.handle());
+        timer.advance_to(start);
+        assert_eq!(interval.poll_tick(&mut cx), Poll::Ready(start));
+        assert_eq!(interval.poll_tick(&mut cx), Poll::Pending);
+        timer.advance_to(start + Duration::from_secs(10));
+        assert_eq!(
+            interval.poll_tick(&mut cx),
+            Poll::Ready(start + Duration::from_secs(10))
+        );
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_timeout() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut timer = Timer::new();
+        let start = Instant::now();
+
+        let mut timeout = pin!(Timeout::new_handle(
+            start + Duration::from_secs(10),
+            pending::<()>(),
+            timer.handle()
+        ));
+        assert!(timeout.as_mut().poll(&mut cx).is_pending());
+        timer.advance_to(start + Duration::from_secs(10));
+        assert!(matches!(
+            timeout.as_mut().poll(&mut cx),
+            Poll::Ready(Err(_))
+        ));
+    }
+
+    #[cfg(feature = "tokio-util")]
+    #[wasm_bindgen_test]
+    pub fn test_delay_queue() {
+        use wasmtimer::tokio_util::DelayQueue;
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut timer = Timer::new();
+
+        let mut delay_queue = DelayQueue::new_handle(timer.handle());
+        let key = delay_queue.insert("value", Duration::from_secs(10));
+        assert!(delay_queue.poll_expired(&mut cx).is_pending());
+        let deadline = timer.next_event().unwrap();
+        timer.advance_to(deadline);
+        match delay_queue.poll_expired(&mut cx) {
+            Poll::Ready(Some(expired)) => {
+                assert_eq!(expired.key(), key);
+                assert_eq!(expired.into_inner(), "value");
+            }
+            other => panic!("expected an expired value, got {other:?}"),
+        }
+    }
+
+    #[wasm_bindgen_test]
+    pub fn test_drop_timer() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let timer = Timer::new();
+        let handle = timer.handle();
+        let start = Instant::now();
+
+        let mut sleep = pin!(Sleep::new_handle(start, handle.clone()));
+        drop(timer);
+        // Sleeps bound to a separately owned timer that was dropped are
+        // cancelled instead of fired, also those created afterwards, while
+        // the default timer never goes away.
+        assert_eq!(sleep.as_mut().poll(&mut cx), Poll::Pending);
+        assert!(!sleep.is_elapsed());
+        let mut sleep = pin!(Sleep::new_handle(start, handle));
+        assert_eq!(sleep.as_mut().poll(&mut cx), Poll::Pending);
+    }
+}
+
+// With `native-fallback`, these also run on native targets against the
+// original `tokio::time` implementation.
+#[cfg(all(
+    feature = "tokio-test-util",
+    any(target_family = "wasm", feature = "native-fallback")
+))]
 pub mod tokio_tests {
     use super::*;
     use std::{pin::Pin, sync::Once, time::Duration};
@@ -25,13 +568,18 @@ pub mod tokio_tests {
 
     static INIT: Once = Once::new();
 
+    // Every native test runs on its own runtime, which needs to be paused.
     pub fn initialize() {
-        INIT.call_once(|| {
+        if cfg!(target_family = "wasm") {
+            INIT.call_once(|| {
+                pause();
+            });
+        } else {
             pause();
-        });
+        }
     }
 
-    #[wasm_bindgen_test]
+    #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
     pub async fn test_micro_second_precision() {
         initialize();
         let a = Instant::now();
@@ -46,6 +594,7 @@ pub mod tokio_tests {
         use super::*;
         use wasmtimer::tokio::sleep;
 
+        // Relies on timers firing inside `advance` without the runtime turning.
         #[wasm_bindgen_test]
         async fn is_elapsed_test() {
             initialize();
@@ -55,47 +604,63 @@ pub mod tokio_tests {
             assert!(slept.is_elapsed());
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn poll_test() {
             initialize();
             let waker = noop_waker_ref();
             let mut cx = Context::from_waker(waker);
 
-            let mut slept = sleep(Duration::from_millis(1000));
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Pending);
+            let mut slept = Box::pin(sleep(Duration::from_millis(1000)));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Pending);
             advance(Duration::from_millis(1005)).await;
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Ready(()));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Ready(()));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn reset_before_exec_test() {
             initialize();
             let waker = noop_waker_ref();
             let mut cx = Context::from_waker(waker);
 
-            let mut slept = sleep(Duration::from_millis(1000));
-            Pin::new(&mut slept).reset(Instant::now() + Duration::from_millis(2000));
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Pending);
+            let mut slept = Box::pin(sleep(Duration::from_millis(1000)));
+            slept
+                .as_mut()
+                .reset(Instant::now() + Duration::from_millis(2000));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Pending);
             advance(Duration::from_millis(1005)).await;
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Pending);
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Pending);
             advance(Duration::from_millis(1005)).await;
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Ready(()));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Ready(()));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn reset_after_exec_test() {
             initialize();
             let waker = noop_waker_ref();
             let mut cx = Context::from_waker(waker);
 
-            let mut slept = sleep(Duration::from_millis(1000));
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Pending);
+            let mut slept = Box::pin(sleep(Duration::from_millis(1000)));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Pending);
             advance(Duration::from_millis(1005)).await;
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Ready(()));
-            Pin::new(&mut slept).reset(Instant::now() + Duration::from_millis(1500));
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Pending);
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Ready(()));
+            slept
+                .as_mut()
+                .reset(Instant::now() + Duration::from_millis(1500));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Pending);
             advance(Duration::from_millis(1505)).await;
-            assert_eq!(Pin::new(&mut slept).poll(&mut cx), Poll::Ready(()));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Ready(()));
+        }
+
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
+        async fn far_future_test() {
+            initialize();
+            let waker = noop_waker_ref();
+            let mut cx = Context::from_waker(waker);
+
+            let mut slept = Box::pin(sleep(Duration::MAX));
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Pending);
+            advance(Duration::from_secs(86400 * 365)).await;
+            assert_eq!(slept.as_mut().poll(&mut cx), Poll::Pending);
         }
     }
 
@@ -105,6 +670,7 @@ pub mod tokio_tests {
 
         use super::*;
 
+        // Relies on timers firing inside `advance` without the runtime turning.
         #[wasm_bindgen_test]
         async fn interval_tick_test() {
             initialize();
@@ -143,7 +709,7 @@ pub mod tokio_tests {
             }
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn interval_at_tick_test() {
             initialize();
 
@@ -183,6 +749,7 @@ pub mod tokio_tests {
             }
         }
 
+        // Relies on timers firing inside `advance` without the runtime turning.
         #[wasm_bindgen_test]
         async fn interval_poll_tick_test() {
             initialize();
@@ -200,7 +767,7 @@ pub mod tokio_tests {
             assert!(matches!(interval.poll_tick(&mut cx), Poll::Ready(_)));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn interval_at_poll_tick_test() {
             initialize();
 
@@ -221,6 +788,7 @@ pub mod tokio_tests {
             assert!(matches!(interval.poll_tick(&mut cx), Poll::Ready(_)));
         }
 
+        // Relies on timers firing inside `advance` without the runtime turning.
         #[wasm_bindgen_test]
         async fn reset_test() {
             initialize();
@@ -240,7 +808,7 @@ pub mod tokio_tests {
             assert!(matches!(interval.poll_tick(&mut cx), Poll::Ready(_)));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn interval_at_reset_test() {
             initialize();
 
@@ -261,7 +829,7 @@ pub mod tokio_tests {
             assert!(matches!(interval.poll_tick(&mut cx), Poll::Ready(_)));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn missed_tick_behavior_burst_test() {
             initialize();
 
@@ -278,7 +846,7 @@ pub mod tokio_tests {
             assert_eq!(interval.poll_tick(&mut cx), Poll::Pending);
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn missed_tick_behavior_skip_test() {
             initialize();
 
@@ -295,7 +863,7 @@ pub mod tokio_tests {
             assert_eq!(interval.poll_tick(&mut cx), Poll::Pending);
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn missed_tick_behavior_delay_test() {
             initialize();
 
@@ -313,6 +881,18 @@ pub mod tokio_tests {
             assert!(matches!(interval.poll_tick(&mut cx), Poll::Ready(_)));
             assert_eq!(interval.poll_tick(&mut cx), Poll::Pending);
         }
+
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
+        async fn interval_far_future_test() {
+            initialize();
+            let waker = noop_waker_ref();
+            let mut cx = Context::from_waker(waker);
+
+            let mut interval = interval(Duration::MAX);
+            interval.tick().await;
+            advance(Duration::from_secs(86400 * 365)).await;
+            assert!(interval.poll_tick(&mut cx).is_pending());
+        }
     }
 
     pub mod timeout_tests {
@@ -320,80 +900,81 @@ pub mod tokio_tests {
         use super::*;
         use wasmtimer::tokio::{sleep, timeout, timeout_at};
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn timeout_success_test() {
             initialize();
 
             let waker = noop_waker_ref();
             let mut cx = Context::from_waker(waker);
 
-            let mut fut = timeout(
+            let mut fut = Box::pin(timeout(
                 Duration::from_millis(1500),
                 sleep(Duration::from_millis(1000)),
-            );
-            assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
-            advance(Duration::from_millis(1001)).await;
-            assert!(matches!(
-                Pin::new(&mut fut).poll(&mut cx),
-                Poll::Ready(Ok(_))
             ));
+            assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+            advance(Duration::from_millis(1001)).await;
+            assert!(matches!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(_))));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn timeout_fail_test() {
             initialize();
 
             let waker = noop_waker_ref();
             let mut cx = Context::from_waker(waker);
 
-            let mut fut = timeout(
+            let mut fut = Box::pin(timeout(
                 Duration::from_millis(1000),
                 sleep(Duration::from_millis(1500)),
-            );
-            assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
-            advance(Duration::from_millis(1001)).await;
-            assert!(matches!(
-                Pin::new(&mut fut).poll(&mut cx),
-                Poll::Ready(Err(_))
             ));
+            assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+            advance(Duration::from_millis(1001)).await;
+            assert!(matches!(fut.as_mut().poll(&mut cx), Poll::Ready(Err(_))));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
+        async fn timeout_far_future_test() {
+            initialize();
+
+            let waker = noop_waker_ref();
+            let mut cx = Context::from_waker(waker);
+
+            let mut fut = Box::pin(timeout(Duration::MAX, sleep(Duration::from_millis(1000))));
+            assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+            advance(Duration::from_millis(1001)).await;
+            assert!(matches!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(_))));
+        }
+
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn timeout_at_success_test() {
             initialize();
 
             let waker = noop_waker_ref();
             let mut cx = Context::from_waker(waker);
 
-            let mut fut = timeout_at(
+            let mut fut = Box::pin(timeout_at(
                 Instant::now() + Duration::from_millis(1500),
                 sleep(Duration::from_millis(1000)),
-            );
-            assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
-            advance(Duration::from_millis(1001)).await;
-            assert!(matches!(
-                Pin::new(&mut fut).poll(&mut cx),
-                Poll::Ready(Ok(_))
             ));
+            assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+            advance(Duration::from_millis(1001)).await;
+            assert!(matches!(fut.as_mut().poll(&mut cx), Poll::Ready(Ok(_))));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn timeout_at_fail_test() {
             initialize();
 
             let waker = noop_waker_ref();
             let mut cx = Context::from_waker(waker);
 
-            let mut fut = timeout_at(
+            let mut fut = Box::pin(timeout_at(
                 Instant::now() + Duration::from_millis(1000),
                 sleep(Duration::from_millis(1500)),
-            );
-            assert_eq!(Pin::new(&mut fut).poll(&mut cx), Poll::Pending);
-            advance(Duration::from_millis(1001)).await;
-            assert!(matches!(
-                Pin::new(&mut fut).poll(&mut cx),
-                Poll::Ready(Err(_))
             ));
+            assert_eq!(fut.as_mut().poll(&mut cx), Poll::Pending);
+            advance(Duration::from_millis(1001)).await;
+            assert!(matches!(fut.as_mut().poll(&mut cx), Poll::Ready(Err(_))));
         }
     }
 
@@ -403,6 +984,7 @@ pub mod tokio_tests {
 
         use super::*;
 
+        // Relies on timers firing inside `advance` without the runtime turning.
         #[wasm_bindgen_test]
         async fn insert_at_test() {
             initialize();
@@ -458,6 +1040,7 @@ pub mod tokio_tests {
             ));
         }
 
+        // Relies on timers firing inside `advance` without the runtime turning.
         #[wasm_bindgen_test]
         async fn insert_test() {
             initialize();
@@ -512,7 +1095,7 @@ pub mod tokio_tests {
             ));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn immidiately_remove_test() {
             initialize();
 
@@ -535,7 +1118,7 @@ pub mod tokio_tests {
             ));
         }
 
-        #[wasm_bindgen_test]
+        #[wasm_bindgen_test(unsupported = tokio_crate::test(crate = "tokio_crate"))]
         async fn remove_after_deadline_test() {
             initialize();
 
@@ -554,6 +1137,7 @@ pub mod tokio_tests {
             ));
         }
 
+        // Relies on timers firing inside `advance` without the runtime turning.
         #[wasm_bindgen_test]
         async fn reset_test() {
             initialize();
@@ -578,5 +1162,52 @@ pub mod tokio_tests {
                 Poll::Ready(None)
             ));
         }
+
+        // `tokio_util` panics on deadlines that do not fit in its wheel.
+        #[wasm_bindgen_test]
+        async fn far_future_test() {
+            initialize();
+
+            let waker = noop_waker_ref();
+            let mut cx = Context::from_waker(waker);
+
+            let mut delay_queue = DelayQueue::<()>::new();
+
+            let key1 = delay_queue.insert((), Duration::MAX);
+            let key2 =
+                delay_queue.insert_at((), Instant::now() + Duration::from_secs(86400 * 3650));
+            assert!(matches!(delay_queue.poll_expired(&mut cx), Poll::Pending));
+            delay_queue.reset(&key2, Duration::MAX);
+            advance(Duration::from_secs(86400 * 365)).await;
+            assert!(matches!(delay_queue.poll_expired(&mut cx), Poll::Pending));
+            delay_queue.remove(&key1);
+            delay_queue.remove(&key2);
+            assert!(delay_queue.is_empty());
+
+            // Deadlines further out than the wheel holds, about 2.2 years, are
+            // kept as they are.
+            let year = Duration::from_secs(86400 * 365);
+            let key3 = delay_queue.insert((), year * 3);
+            let key4 = delay_queue.insert((), year * 10);
+            advance(year * 3 - Duration::from_secs(1)).await;
+            assert!(matches!(delay_queue.poll_expired(&mut cx), Poll::Pending));
+            advance(Duration::from_secs(2)).await;
+            assert!(matches!(
+                delay_queue.poll_expired(&mut cx),
+                Poll::Ready(Some(expired)) if expired.key() == key3
+            ));
+            assert!(matches!(delay_queue.poll_expired(&mut cx), Poll::Pending));
+            advance(year * 7 - Duration::from_secs(2)).await;
+            assert!(matches!(delay_queue.poll_expired(&mut cx), Poll::Pending));
+            advance(Duration::from_secs(2)).await;
+            assert!(matches!(
+                delay_queue.poll_expired(&mut cx),
+                Poll::Ready(Some(expired)) if expired.key() == key4
+            ));
+            assert!(matches!(
+                delay_queue.poll_expired(&mut cx),
+                Poll::Ready(None)
+            ));
+        }
     }
 }